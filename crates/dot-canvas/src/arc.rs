@@ -1,3 +1,8 @@
+use crate::shape::STEP;
+use crate::BoundingBox;
+use crate::EllipticalArc;
+use crate::Outline;
+use crate::Raster;
 use crate::Shape;
 
 pub struct Arc {
@@ -28,14 +33,26 @@ impl Arc {
         }
     }
 
+    /// the distance between the start and end point
+    fn chord(&self) -> f32 {
+        ((self.x2 - self.x1).powf(2.0) + (self.y2 - self.y1).powf(2.0)).sqrt()
+    }
+
+    /// the radius used for drawing, which is scaled up to half of the chord
+    /// when the radius is too small to span the start and end point
+    pub fn effective_radius(&self) -> f32 {
+        self.radius.abs().max(self.chord() / 2.0)
+    }
+
     /// calculate the center of this arc given start point, end point, radius and sweep direction
     pub fn center(&self) -> (f32, f32) {
-        let q = ((self.x2 - self.x1).powf(2.0) + (self.y2 - self.y1).powf(2.0))
-            .sqrt();
+        let q = self.chord();
         let y3 = (self.y1 + self.y2) / 2.0;
         let x3 = (self.x1 + self.x2) / 2.0;
 
-        let rr_q22 = (self.radius.powf(2.0) - (q / 2.0).powf(2.0)).sqrt();
+        let rr_q22 = (self.radius.powf(2.0) - (q / 2.0).powf(2.0))
+            .max(0.0)
+            .sqrt();
 
         let base_x = rr_q22 * (self.y1 - self.y2) / q;
         let base_y = rr_q22 * (self.x2 - self.x1) / q;
@@ -51,6 +68,22 @@ impl Arc {
         }
     }
 
+    /// The same arc as the svg arc command with the radius, small arc and
+    /// sweep flag, which is used for the outline and rasterizing
+    fn to_elliptical_arc(&self) -> EllipticalArc {
+        EllipticalArc::new(
+            self.x1,
+            self.y1,
+            self.x2,
+            self.y2,
+            self.radius,
+            self.radius,
+            0.0,
+            false,
+            self.sweep_flag,
+        )
+    }
+
     /// which octant range the arc lies
     fn octant(&self) -> (u8, u8) {
         let (cx, cy) = self.center();
@@ -88,12 +121,17 @@ impl Arc {
 
 impl<'a> Shape<'a> for Arc {
     fn points(&'a self) -> Box<dyn Iterator<Item = (f32, f32)> + 'a> {
-        let mut x = self.radius;
+        self.points_at_resolution(STEP)
+    }
+
+    fn points_at_resolution(
+        &'a self,
+        inc: f32,
+    ) -> Box<dyn Iterator<Item = (f32, f32)> + 'a> {
+        let mut x = self.effective_radius();
         let mut y = 0.0;
         let mut err = 0.0;
 
-        let inc = 0.25;
-
         let mut points = vec![];
 
         let (cx, cy) = self.center();
@@ -137,6 +175,26 @@ impl<'a> Shape<'a> for Arc {
         }
         Box::new(points.into_iter())
     }
+
+    fn outlines(&'a self, dot_size: f32) -> Vec<Outline> {
+        self.to_elliptical_arc().outlines(dot_size)
+    }
+
+    fn rasterize(&'a self, raster: &mut Raster) {
+        self.to_elliptical_arc().rasterize(raster)
+    }
+
+    /// the box around the whole circle, which contains the arc, None when
+    /// the center or the radius is not finite
+    fn bounding_box(&'a self) -> Option<BoundingBox> {
+        let (cx, cy) = self.center();
+        let r = self.effective_radius().abs();
+        let corners = [(cx - r, cy - r), (cx + r, cy + r)];
+        if !corners.iter().all(|(x, y)| x.is_finite() && y.is_finite()) {
+            return None;
+        }
+        BoundingBox::from_points(corners)
+    }
 }
 
 #[cfg(test)]
@@ -174,6 +232,13 @@ mod tests {
         assert_eq!(center, (0.0, 10.0));
     }
 
+    #[test]
+    fn arc_center_small_radius() {
+        let arc = Arc::new(0.0, 0.0, 10.0, 0.0, 2.0, false);
+        assert_eq!(arc.center(), (5.0, 0.0));
+        assert_eq!(arc.effective_radius(), 5.0);
+    }
+
     #[test]
     fn draw_arc() {
         let width = 11.0;
@@ -193,17 +258,17 @@ mod tests {
         let result = context.to_string();
         println!("{}", result);
         let expected = [
+            "⡇                     ",
+            "⡇                     ",
             "⢱                     ",
-            "⢸                     ",
-            " ⡇                    ",
-            " ⠸⡀                   ",
-            "  ⠱⡀                  ",
-            "   ⠑⡄                 ",
-            "    ⠈⠢⡀               ",
-            "      ⠈⠢⡀             ",
-            "        ⠈⠑⠤⣀          ",
-            "            ⠉⠒⠢⠤⢄⣀⣀⣀  ",
-            "                    ⠁ ",
+            " ⢇                    ",
+            " ⠈⢆                   ",
+            "  ⠈⢆                  ",
+            "    ⠑⡄                ",
+            "     ⠈⠒⢄              ",
+            "        ⠑⠢⢄⡀          ",
+            "           ⠈⠑⠒⠤⢄⣀⣀⣀⣀  ",
+            "                      ",
         ];
         let expected = expected.join("\n");
         assert_eq!(result, expected);
@@ -228,27 +293,27 @@ mod tests {
         let result = context.to_string();
         println!("{}", result);
         let expected = [
-            "             ⣀⡠⠤⠔⠒⠒⠒⠁                       ",
-            "         ⢀⠤⠒⠉                               ",
-            "       ⡠⠊⠁                                  ",
-            "     ⡠⠊                                     ",
-            "   ⢀⠎                                       ",
-            "  ⢠⠃                                        ",
-            " ⢠⠃                                         ",
+            "           ⢀⡠⠤⠒⠊⠉⠉⠉⠉                        ",
+            "        ⡠⠔⠊⠁                                ",
+            "     ⢀⠤⠊                                    ",
+            "    ⡠⠃                                      ",
+            "  ⢀⠎                                        ",
+            " ⢀⠎                                         ",
             " ⡎                                          ",
-            "⢰⠁                                          ",
-            "⢸                                           ",
+            "⡸                                           ",
+            "⡇                                           ",
+            "⡇                                           ",
+            "⡇                                           ",
+            "⡇                                           ",
             "⢱                                           ",
-            "⢸                                           ",
-            " ⡇                                          ",
-            " ⠸⡀                                         ",
-            "  ⠱⡀                                        ",
-            "   ⠑⡄                                       ",
-            "    ⠈⠢⡀                                     ",
-            "      ⠈⠢⡀                                   ",
-            "        ⠈⠑⠤⣀                                ",
-            "            ⠉⠒⠢⠤⢄⣀⣀⣀                        ",
-            "                    ⠁                       ",
+            " ⢇                                          ",
+            " ⠈⢆                                         ",
+            "  ⠈⢆                                        ",
+            "    ⠑⡄                                      ",
+            "     ⠈⠒⢄                                    ",
+            "        ⠑⠢⢄⡀                                ",
+            "           ⠈⠑⠒⠤⢄⣀⣀⣀⣀                        ",
+            "                                            ",
             "                                            ",
         ];
         let expected = expected.join("\n");
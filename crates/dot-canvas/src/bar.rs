@@ -0,0 +1,225 @@
+//! Bar charts and histograms, where the length of the bars has the
+//! resolution of a single dot
+use crate::Color;
+use crate::Context;
+use crate::FilledRectangle;
+use crate::Orientation;
+
+/// The direction the bars grow in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BarDirection {
+    /// the bars grow up from the bottom, in the order left to right
+    Vertical,
+    /// the bars grow right from the left, in the order top to bottom
+    Horizontal,
+}
+
+/// A bar for each value, filling the whole context
+pub struct BarChart {
+    pub values: Vec<f32>,
+    pub direction: BarDirection,
+    /// the number of empty dots in between the bars
+    pub gap: usize,
+    /// the value of a bar spanning the whole length, instead of the largest
+    /// value. A max that is not finite is ignored.
+    pub max: Option<f32>,
+    pub color: Option<Color>,
+}
+
+/// The number of samples in each of the equally wide bins spanning a range
+pub struct Histogram {
+    /// the number of samples in each bin
+    pub counts: Vec<usize>,
+    /// the start and end of the range of the bins
+    pub range: (f32, f32),
+    pub direction: BarDirection,
+    pub color: Option<Color>,
+}
+
+impl BarChart {
+    pub fn new(values: &[f32], direction: BarDirection) -> Self {
+        BarChart {
+            values: values.to_vec(),
+            direction,
+            gap: 1,
+            max: None,
+            color: None,
+        }
+    }
+
+    /// Draw the bars into the whole context, which has its bounds set to
+    /// the dots of the grid.
+    /// Negative values grow from the baseline in the other direction.
+    pub fn draw(&self, context: &mut Context) {
+        let (dots_wide, dots_high) = context.grid().resolution();
+        context.set_x_bounds(0.0, dots_wide as f32);
+        context.set_y_bounds(0.0, dots_high as f32);
+        context.set_orientation(Orientation::YUp);
+        let (across, along) = match self.direction {
+            BarDirection::Vertical => (dots_wide, dots_high),
+            BarDirection::Horizontal => (dots_high, dots_wide),
+        };
+        let finite = self.values.iter().filter(|v| v.is_finite());
+        let high = match self.max {
+            Some(max) if max.is_finite() => max,
+            _ => finite.clone().fold(0.0, |a: f32, b| a.max(*b)),
+        };
+        let low = finite.fold(0.0, |a: f32, b| a.min(*b));
+        if self.values.is_empty() || high <= low {
+            return;
+        }
+        // the dots along the bars, from the lowest value to the highest
+        let scale = along as f32 / (high - low);
+        let baseline = (-low * scale).round();
+        let n = self.values.len();
+        for (i, value) in self.values.iter().enumerate() {
+            if !value.is_finite() {
+                continue;
+            }
+            let start = i * across / n;
+            let mut end = (i + 1) * across / n;
+            if end - start > self.gap {
+                end -= self.gap;
+            }
+            let length = (value.clamp(low, high) * scale).round();
+            let (from, to) = if length < 0.0 {
+                (baseline + length, baseline)
+            } else {
+                (baseline, baseline + length)
+            };
+            let (start, end) = (start as f32, end as f32);
+            let rect = match self.direction {
+                BarDirection::Vertical => {
+                    FilledRectangle::new(start, from, end, to)
+                }
+                // the first bar is at the top
+                BarDirection::Horizontal => FilledRectangle::new(
+                    from,
+                    across as f32 - end,
+                    to,
+                    across as f32 - start,
+                ),
+            };
+            match self.color {
+                Some(color) => context.draw_with_color(&rect, color),
+                None => context.draw(&rect),
+            }
+        }
+    }
+}
+
+impl Histogram {
+    /// Count the finite samples into the number of bins spanning from the
+    /// smallest to the largest sample
+    pub fn new(samples: &[f32], bins: usize, direction: BarDirection) -> Self {
+        let finite = samples.iter().filter(|s| s.is_finite());
+        let min = finite.clone().fold(f32::INFINITY, |a, b| a.min(*b));
+        let max = finite.fold(f32::NEG_INFINITY, |a, b| a.max(*b));
+        let range = if min > max { (0.0, 1.0) } else { (min, max) };
+        Histogram::with_range(samples, bins, range, direction)
+    }
+
+    /// Count the samples into the number of bins spanning the range, the
+    /// samples outside of the range are left out
+    pub fn with_range(
+        samples: &[f32],
+        bins: usize,
+        range: (f32, f32),
+        direction: BarDirection,
+    ) -> Self {
+        let mut counts = vec![0; bins];
+        let (start, end) = range;
+        for sample in samples.iter() {
+            if bins == 0 || !(start..=end).contains(sample) {
+                continue;
+            }
+            let bin = if end > start {
+                ((sample - start) / (end - start) * bins as f32) as usize
+            } else {
+                0
+            };
+            // the end of the range is part of the last bin
+            counts[bin.min(bins - 1)] += 1;
+        }
+        Histogram {
+            counts,
+            range,
+            direction,
+            color: None,
+        }
+    }
+
+    /// the start and end of each bin
+    pub fn bin_edges(&self) -> Vec<(f32, f32)> {
+        let (start, end) = self.range;
+        let width = (end - start) / self.counts.len() as f32;
+        (0..self.counts.len())
+            .map(|i| (start + i as f32 * width, start + (i + 1) as f32 * width))
+            .collect()
+    }
+
+    /// Draw the counts as adjacent bars into the whole context
+    pub fn draw(&self, context: &mut Context) {
+        let values: Vec<f32> = self.counts.iter().map(|c| *c as f32).collect();
+        let mut chart = BarChart::new(&values, self.direction);
+        chart.gap = 0;
+        chart.color = self.color;
+        chart.draw(context);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vertical_bars() {
+        let chart =
+            BarChart::new(&[1.0, 2.0, 3.0, 7.0, 8.0], BarDirection::Vertical);
+        let mut context = Context::with_bounds(5, 2, (0.0, 1.0), (0.0, 1.0));
+        chart.draw(&mut context);
+        let result = context.to_string();
+        println!("{}", result);
+        let expected = ["   ⡆⡇", "⡀⡄⡆⡇⡇"];
+        assert_eq!(result, expected.join("\n"));
+    }
+
+    #[test]
+    fn non_finite_max_is_ignored() {
+        let mut chart =
+            BarChart::new(&[1.0, 2.0, 3.0, 7.0, 8.0], BarDirection::Vertical);
+        chart.max = Some(f32::NAN);
+        let mut context = Context::with_bounds(5, 2, (0.0, 1.0), (0.0, 1.0));
+        chart.draw(&mut context);
+        let result = context.to_string();
+        let expected = ["   ⡆⡇", "⡀⡄⡆⡇⡇"];
+        assert_eq!(result, expected.join("\n"));
+    }
+
+    #[test]
+    fn horizontal_negative_bars() {
+        let mut chart =
+            BarChart::new(&[2.0, -1.0, 4.0, -4.0], BarDirection::Horizontal);
+        chart.gap = 0;
+        let mut context = Context::with_bounds(4, 1, (0.0, 1.0), (0.0, 1.0));
+        chart.draw(&mut context);
+        let result = context.to_string();
+        println!("{}", result);
+        let expected = ["⣀⣐⠭⠤"];
+        assert_eq!(result, expected.join("\n"));
+    }
+
+    #[test]
+    fn histogram_bins() {
+        let samples = [0.0, 0.5, 1.0, 1.5, 2.5, 3.9, 4.0, f32::NAN];
+        let histogram = Histogram::new(&samples, 4, BarDirection::Vertical);
+        assert_eq!(histogram.counts, vec![2, 2, 1, 2]);
+        assert_eq!(histogram.bin_edges()[1], (1.0, 2.0));
+        let mut context = Context::with_bounds(4, 1, (0.0, 1.0), (0.0, 1.0));
+        histogram.draw(&mut context);
+        let result = context.to_string();
+        println!("{}", result);
+        let expected = ["⣿⣿⣤⣿"];
+        assert_eq!(result, expected.join("\n"));
+    }
+}
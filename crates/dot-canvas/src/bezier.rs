@@ -0,0 +1,246 @@
+use crate::shape::STEP;
+use crate::BoundingBox;
+use crate::Outline;
+use crate::Shape;
+
+/// the maximum number of times a curve is subdivided
+const MAX_DEPTH: u32 = 16;
+
+/// A quadratic bézier curve from (x1, y1) to (x2, y2) with control point
+/// (cx, cy)
+pub struct QuadBezier {
+    pub x1: f32,
+    pub y1: f32,
+    pub cx: f32,
+    pub cy: f32,
+    pub x2: f32,
+    pub y2: f32,
+}
+
+/// A cubic bézier curve from (x1, y1) to (x2, y2) with control points
+/// (cx1, cy1) and (cx2, cy2)
+pub struct CubicBezier {
+    pub x1: f32,
+    pub y1: f32,
+    pub cx1: f32,
+    pub cy1: f32,
+    pub cx2: f32,
+    pub cy2: f32,
+    pub x2: f32,
+    pub y2: f32,
+}
+
+impl QuadBezier {
+    pub fn new(
+        (x1, y1): (f32, f32),
+        (cx, cy): (f32, f32),
+        (x2, y2): (f32, f32),
+    ) -> Self {
+        QuadBezier {
+            x1,
+            y1,
+            cx,
+            cy,
+            x2,
+            y2,
+        }
+    }
+
+    /// the equivalent cubic curve, which has the same shape
+    pub fn to_cubic(&self) -> CubicBezier {
+        let c1 = lerp((self.x1, self.y1), (self.cx, self.cy), 2.0 / 3.0);
+        let c2 = lerp((self.x2, self.y2), (self.cx, self.cy), 2.0 / 3.0);
+        CubicBezier::new((self.x1, self.y1), c1, c2, (self.x2, self.y2))
+    }
+}
+
+impl CubicBezier {
+    pub fn new(
+        (x1, y1): (f32, f32),
+        (cx1, cy1): (f32, f32),
+        (cx2, cy2): (f32, f32),
+        (x2, y2): (f32, f32),
+    ) -> Self {
+        CubicBezier {
+            x1,
+            y1,
+            cx1,
+            cy1,
+            cx2,
+            cy2,
+            x2,
+            y2,
+        }
+    }
+
+    /// Flatten the curve into a polyline whose vertices deviate from the
+    /// curve by no more than `tolerance`
+    pub fn flatten(&self, tolerance: f32) -> Vec<(f32, f32)> {
+        let mut vertices = vec![(self.x1, self.y1)];
+        subdivide(
+            [
+                (self.x1, self.y1),
+                (self.cx1, self.cy1),
+                (self.cx2, self.cy2),
+                (self.x2, self.y2),
+            ],
+            tolerance,
+            0,
+            &mut vertices,
+        );
+        vertices
+    }
+
+    /// The points of the curve, where consecutive points are at most
+    /// `dot_size` apart
+    fn sample(&self, dot_size: f32) -> Vec<(f32, f32)> {
+        let vertices = self.flatten(dot_size / 2.0);
+        let mut points = vec![];
+        for pair in vertices.windows(2) {
+            let (p0, p1) = (pair[0], pair[1]);
+            let length = distance(p0, p1);
+            let n = (length / dot_size).ceil().max(1.0) as usize;
+            points.extend((0..n).map(|i| lerp(p0, p1, i as f32 / n as f32)));
+        }
+        points.push((self.x2, self.y2));
+        points
+    }
+}
+
+fn lerp(p0: (f32, f32), p1: (f32, f32), t: f32) -> (f32, f32) {
+    (p0.0 + (p1.0 - p0.0) * t, p0.1 + (p1.1 - p0.1) * t)
+}
+
+fn distance(p0: (f32, f32), p1: (f32, f32)) -> f32 {
+    ((p1.0 - p0.0).powi(2) + (p1.1 - p0.1).powi(2)).sqrt()
+}
+
+/// distance of point p from the line passing through a and b
+fn distance_to_line(p: (f32, f32), a: (f32, f32), b: (f32, f32)) -> f32 {
+    let length = distance(a, b);
+    if length == 0.0 {
+        distance(p, a)
+    } else {
+        ((b.0 - a.0) * (a.1 - p.1) - (a.0 - p.0) * (b.1 - a.1)).abs() / length
+    }
+}
+
+/// Recursively split the curve in half with de Casteljau's algorithm until
+/// the control points are within the tolerance of the chord, pushing the
+/// end point of each flat piece
+fn subdivide(
+    curve: [(f32, f32); 4],
+    tolerance: f32,
+    depth: u32,
+    vertices: &mut Vec<(f32, f32)>,
+) {
+    let [p0, p1, p2, p3] = curve;
+    let flatness =
+        distance_to_line(p1, p0, p3).max(distance_to_line(p2, p0, p3));
+    if flatness <= tolerance || depth >= MAX_DEPTH {
+        vertices.push(p3);
+        return;
+    }
+    let p01 = lerp(p0, p1, 0.5);
+    let p12 = lerp(p1, p2, 0.5);
+    let p23 = lerp(p2, p3, 0.5);
+    let p012 = lerp(p01, p12, 0.5);
+    let p123 = lerp(p12, p23, 0.5);
+    let mid = lerp(p012, p123, 0.5);
+    subdivide([p0, p01, p012, mid], tolerance, depth + 1, vertices);
+    subdivide([mid, p123, p23, p3], tolerance, depth + 1, vertices);
+}
+
+impl<'a> Shape<'a> for QuadBezier {
+    fn points(&'a self) -> Box<dyn Iterator<Item = (f32, f32)> + 'a> {
+        self.points_at_resolution(STEP)
+    }
+
+    fn points_at_resolution(
+        &'a self,
+        dot_size: f32,
+    ) -> Box<dyn Iterator<Item = (f32, f32)> + 'a> {
+        Box::new(self.to_cubic().sample(dot_size).into_iter())
+    }
+
+    fn outlines(&'a self, dot_size: f32) -> Vec<Outline> {
+        vec![Outline::open(self.to_cubic().flatten(dot_size / 2.0))]
+    }
+
+    /// the box around the control points, which contains the curve
+    fn bounding_box(&'a self) -> Option<BoundingBox> {
+        BoundingBox::from_points(vec![
+            (self.x1, self.y1),
+            (self.cx, self.cy),
+            (self.x2, self.y2),
+        ])
+    }
+}
+
+impl<'a> Shape<'a> for CubicBezier {
+    fn points(&'a self) -> Box<dyn Iterator<Item = (f32, f32)> + 'a> {
+        self.points_at_resolution(STEP)
+    }
+
+    fn points_at_resolution(
+        &'a self,
+        dot_size: f32,
+    ) -> Box<dyn Iterator<Item = (f32, f32)> + 'a> {
+        Box::new(self.sample(dot_size).into_iter())
+    }
+
+    fn outlines(&'a self, dot_size: f32) -> Vec<Outline> {
+        vec![Outline::open(self.flatten(dot_size / 2.0))]
+    }
+
+    /// the box around the control points, which contains the curve
+    fn bounding_box(&'a self) -> Option<BoundingBox> {
+        BoundingBox::from_points(vec![
+            (self.x1, self.y1),
+            (self.cx1, self.cy1),
+            (self.cx2, self.cy2),
+            (self.x2, self.y2),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn straight_curve_is_not_subdivided() {
+        let curve =
+            CubicBezier::new((0.0, 0.0), (1.0, 0.0), (2.0, 0.0), (3.0, 0.0));
+        assert_eq!(curve.flatten(0.1), vec![(0.0, 0.0), (3.0, 0.0)]);
+    }
+
+    #[test]
+    fn finer_resolution_has_no_gaps() {
+        let curve = CubicBezier::new(
+            (0.0, 0.0),
+            (0.0, 10.0),
+            (10.0, 10.0),
+            (10.0, 0.0),
+        );
+        for &dot_size in &[0.25, 0.05] {
+            let points: Vec<(f32, f32)> =
+                curve.points_at_resolution(dot_size).collect();
+            assert!(points
+                .windows(2)
+                .all(|pair| distance(pair[0], pair[1]) <= dot_size + 1e-4));
+            assert_eq!(points.last(), Some(&(10.0, 0.0)));
+        }
+    }
+
+    #[test]
+    fn draw_quad_bezier() {
+        let mut context = Context::new(4.0, 2.0);
+        context.draw(&QuadBezier::new((0.0, 1.75), (2.0, -1.5), (4.0, 1.75)));
+        let result = context.to_string();
+        println!("{}", result);
+        let expected = [" ⡠⠒⠉⠉⠓⢄ ", "⡔⠁    ⠈⠢"];
+        assert_eq!(result, expected.join("\n"));
+    }
+}
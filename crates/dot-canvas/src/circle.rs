@@ -1,4 +1,11 @@
+use crate::ellipse::sample_ellipse;
+use crate::shape::valid_step;
+use crate::shape::STEP;
+use crate::BoundingBox;
+use crate::Outline;
+use crate::Raster;
 use crate::Shape;
+use std::f32::consts::PI;
 
 pub struct Circle {
     pub x: f32,
@@ -14,12 +21,21 @@ impl Circle {
 
 impl<'a> Shape<'a> for Circle {
     fn points(&'a self) -> Box<dyn Iterator<Item = (f32, f32)> + 'a> {
+        self.points_at_resolution(STEP)
+    }
+
+    fn points_at_resolution(
+        &'a self,
+        inc: f32,
+    ) -> Box<dyn Iterator<Item = (f32, f32)> + 'a> {
+        let inc = valid_step(inc);
+        if !self.radius.is_finite() {
+            return Box::new(std::iter::empty());
+        }
         let mut x = self.radius;
         let mut y = 0.0;
         let mut err = 0.0;
 
-        let inc = 0.25;
-
         let mut points = vec![];
 
         while x >= y {
@@ -44,6 +60,37 @@ impl<'a> Shape<'a> for Circle {
         }
         Box::new(points.into_iter())
     }
+
+    fn outlines(&'a self, dot_size: f32) -> Vec<Outline> {
+        let r = self.radius.abs();
+        let mut vertices =
+            sample_ellipse(self.x, self.y, r, r, 0.0, 0.0, 2.0 * PI, dot_size);
+        vertices.pop();
+        vec![Outline::closed(vertices)]
+    }
+
+    fn rasterize(&'a self, raster: &mut Raster) {
+        raster.ellipse(self.x, self.y, self.radius, self.radius);
+    }
+
+    fn bounding_box(&'a self) -> Option<BoundingBox> {
+        let r = self.radius.abs();
+        Some(BoundingBox::new(
+            self.x - r,
+            self.y - r,
+            self.x + r,
+            self.y + r,
+        ))
+    }
+
+    fn distance(&'a self, x: f32, y: f32) -> f32 {
+        let center = ((x - self.x).powi(2) + (y - self.y).powi(2)).sqrt();
+        (center - self.radius.abs()).abs()
+    }
+
+    fn contains(&'a self, x: f32, y: f32) -> bool {
+        (x - self.x).powi(2) + (y - self.y).powi(2) <= self.radius.powi(2)
+    }
 }
 
 #[cfg(test)]
@@ -51,6 +98,24 @@ mod tests {
     use super::*;
     use crate::*;
 
+    #[test]
+    fn invalid_steps_fall_back_to_the_default() {
+        let circle = Circle::new(0.0, 0.0, 1.0);
+        let expected = circle.points_at_resolution(STEP).count();
+        for step in [0.0, -1.0, f32::NAN, f32::INFINITY] {
+            assert_eq!(circle.points_at_resolution(step).count(), expected);
+        }
+        let infinite = Circle::new(0.0, 0.0, f32::INFINITY);
+        assert_eq!(infinite.points().count(), 0);
+
+        let line = Line::new(0.0, 0.0, 1.0, 0.0);
+        assert_eq!(line.iter_with_step(0.0).count(), 4);
+        assert_eq!(line.iter_with_step(-0.5).count(), 4);
+        assert_eq!(line.iter_with_step(f32::NAN).count(), 4);
+        let infinite = Line::new(0.0, 0.0, f32::INFINITY, 0.0);
+        assert_eq!(infinite.iter_with_step(0.25).count(), 0);
+    }
+
     #[test]
     fn draw_circle4() {
         let width = 10.0;
@@ -67,15 +132,15 @@ mod tests {
 
         let expected = [
             "                    ",
-            "      ⣀⠤⠒⠒⠑⠒⠢⢄⡀     ",
-            "    ⡠⠊        ⠈⠢⡀   ",
-            "   ⡜            ⠘⡄  ",
-            "  ⢸              ⢸  ",
-            "  ⢱              ⢰⠁ ",
-            "  ⠈⢆            ⢀⠎  ",
-            "   ⠈⠢⡀         ⡠⠊   ",
-            "     ⠈⠒⠤⣀⣀⢀⣀⡠⠔⠊     ",
-            "          ⠁         ",
+            "    ⢀⡠⠒⠉⠉⠉⠉⠉⠉⠒⢄⡀    ",
+            "   ⡰⠁          ⠈⢆   ",
+            "  ⡜              ⢣  ",
+            "  ⡇              ⢸  ",
+            "  ⡇              ⢸  ",
+            "  ⢣              ⡜  ",
+            "   ⠱⡀          ⢀⠎   ",
+            "    ⠈⠑⠤⣀⣀⣀⣀⣀⣀⠤⠊⠁    ",
+            "                    ",
         ];
         assert_eq!(result, expected.join("\n"));
     }
@@ -94,26 +159,26 @@ mod tests {
         println!("{}", result);
 
         let expected = [
-            "             ⣀⡠⠤⠔⠒⠒⠒⠑⠒⠒⠒⠤⠤⣀⡀            ",
-            "         ⢀⠤⠒⠉              ⠈⠑⠢⢄         ",
-            "       ⡠⠊⠁                     ⠉⠢⡀      ",
-            "     ⡠⠊                          ⠈⠢⡀    ",
-            "   ⢀⠎                              ⠈⢆   ",
-            "  ⢠⠃                                 ⢣  ",
-            " ⢠⠃                                   ⢣ ",
-            " ⡎                                    ⠈⡆",
-            "⢰⠁                                     ⢱",
-            "⢸                                      ⢸",
-            "⢱                                      ⢰",
-            "⢸                                      ⢸",
-            " ⡇                                     ⡇",
-            " ⠸⡀                                   ⡸ ",
-            "  ⠱⡀                                 ⡰⠁ ",
-            "   ⠑⡄                               ⡔⠁  ",
-            "    ⠈⠢⡀                           ⡠⠊    ",
-            "      ⠈⠢⡀                       ⡠⠊      ",
-            "        ⠈⠑⠤⣀                ⢀⡠⠔⠉        ",
-            "            ⠉⠒⠢⠤⢄⣀⣀⣀⢀⣀⣀⣀⠤⠤⠒⠊⠁           ",
+            "           ⢀⡠⠤⠒⠊⠉⠉⠉⠉⠉⠉⠉⠉⠑⠒⠤⢄⡀           ",
+            "        ⡠⠔⠊⠁                ⠈⠑⠢⢄        ",
+            "     ⢀⠤⠊                        ⠑⠤⡀     ",
+            "    ⡠⠃                            ⠘⢄    ",
+            "  ⢀⠎                                ⠱⡀  ",
+            " ⢀⠎                                  ⠱⡀ ",
+            " ⡎                                    ⢱ ",
+            "⡸                                      ⢇",
+            "⡇                                      ⢸",
+            "⡇                                      ⢸",
+            "⡇                                      ⢸",
+            "⡇                                      ⢸",
+            "⢱                                      ⡎",
+            " ⢇                                    ⡸ ",
+            " ⠈⢆                                  ⡰⠁ ",
+            "  ⠈⢆                                ⡰⠁  ",
+            "    ⠑⡄                            ⢠⠊    ",
+            "     ⠈⠒⢄                        ⡠⠒⠁     ",
+            "        ⠑⠢⢄⡀                ⢀⡠⠔⠊        ",
+            "           ⠈⠑⠒⠤⢄⣀⣀⣀⣀⣀⣀⣀⣀⡠⠤⠒⠊⠁           ",
         ];
         assert_eq!(result, expected.join("\n"));
     }
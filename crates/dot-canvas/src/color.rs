@@ -0,0 +1,187 @@
+/// The 16 basic ansi colors in rgb, used when converting colors between
+/// the different color modes
+const ANSI_16_RGB: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (128, 0, 0),
+    (0, 128, 0),
+    (128, 128, 0),
+    (0, 0, 128),
+    (128, 0, 128),
+    (0, 128, 128),
+    (192, 192, 192),
+    (128, 128, 128),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (0, 0, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+/// The intensity levels of each component of the 6x6x6 color cube in the
+/// 256 color palette
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// Foreground color of a braille cell
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    /// one of the 16 basic ansi colors, 0 to 15
+    Ansi(u8),
+    /// one of the 256 colors of the xterm palette
+    Ansi256(u8),
+    /// 24 bit truecolor
+    Rgb(u8, u8, u8),
+}
+
+/// Determines which escape sequences are emitted when rendering colors
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorMode {
+    /// only the 16 basic ansi colors, `ESC[30m`..`ESC[97m`
+    Ansi16,
+    /// the xterm 256 color palette, `ESC[38;5;Nm`
+    Ansi256,
+    /// 24 bit colors, `ESC[38;2;R;G;Bm`
+    TrueColor,
+}
+
+impl Color {
+    pub const BLACK: Color = Color::Ansi(0);
+    pub const RED: Color = Color::Ansi(1);
+    pub const GREEN: Color = Color::Ansi(2);
+    pub const YELLOW: Color = Color::Ansi(3);
+    pub const BLUE: Color = Color::Ansi(4);
+    pub const MAGENTA: Color = Color::Ansi(5);
+    pub const CYAN: Color = Color::Ansi(6);
+    pub const WHITE: Color = Color::Ansi(7);
+
+    /// the rgb value of this color
+    pub fn to_rgb(&self) -> (u8, u8, u8) {
+        match *self {
+            Color::Ansi(n) => ANSI_16_RGB[n as usize % 16],
+            Color::Ansi256(n) if n < 16 => ANSI_16_RGB[n as usize],
+            Color::Ansi256(n) if n < 232 => {
+                let n = n - 16;
+                (
+                    CUBE_LEVELS[(n / 36) as usize],
+                    CUBE_LEVELS[(n / 6 % 6) as usize],
+                    CUBE_LEVELS[(n % 6) as usize],
+                )
+            }
+            Color::Ansi256(n) => {
+                let level = 8 + (n - 232) * 10;
+                (level, level, level)
+            }
+            Color::Rgb(r, g, b) => (r, g, b),
+        }
+    }
+
+    /// the nearest color in the 16 basic ansi colors
+    pub fn to_ansi16(&self) -> u8 {
+        match *self {
+            Color::Ansi(n) => n % 16,
+            Color::Ansi256(n) if n < 16 => n,
+            _ => nearest(&ANSI_16_RGB, self.to_rgb()),
+        }
+    }
+
+    /// the nearest color in the xterm 256 color palette
+    pub fn to_ansi256(&self) -> u8 {
+        match *self {
+            Color::Ansi(n) => n % 16,
+            Color::Ansi256(n) => n,
+            Color::Rgb(r, g, b) => {
+                let cube_index = |c: u8| nearest_level(&CUBE_LEVELS, c) as u8;
+                let (ri, gi, bi) =
+                    (cube_index(r), cube_index(g), cube_index(b));
+                let cube = 16 + 36 * ri + 6 * gi + bi;
+                let avg = (r as u16 + g as u16 + b as u16) / 3;
+                let gray = if avg < 8 {
+                    232
+                } else {
+                    (232 + (avg - 8 + 5) / 10).min(255) as u8
+                };
+                let rgb = (r, g, b);
+                if distance(Color::Ansi256(gray).to_rgb(), rgb)
+                    < distance(Color::Ansi256(cube).to_rgb(), rgb)
+                {
+                    gray
+                } else {
+                    cube
+                }
+            }
+        }
+    }
+
+    /// the escape sequence that sets this color as the foreground color
+    pub fn fg_escape(&self, mode: ColorMode) -> String {
+        match mode {
+            ColorMode::Ansi16 => {
+                let n = self.to_ansi16();
+                if n < 8 {
+                    format!("\x1b[{}m", 30 + n)
+                } else {
+                    format!("\x1b[{}m", 90 + n - 8)
+                }
+            }
+            ColorMode::Ansi256 => format!("\x1b[38;5;{}m", self.to_ansi256()),
+            ColorMode::TrueColor => {
+                let (r, g, b) = self.to_rgb();
+                format!("\x1b[38;2;{};{};{}m", r, g, b)
+            }
+        }
+    }
+}
+
+/// squared euclidean distance of 2 rgb colors
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2) as u32;
+    d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+}
+
+/// index of the palette entry closest to the rgb color
+fn nearest(palette: &[(u8, u8, u8)], rgb: (u8, u8, u8)) -> u8 {
+    palette
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, c)| distance(**c, rgb))
+        .map(|(i, _)| i as u8)
+        .unwrap_or(0)
+}
+
+/// index of the level closest to the color component
+fn nearest_level(levels: &[u8], c: u8) -> usize {
+    levels
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, l)| (**l as i32 - c as i32).abs())
+        .map(|(i, _)| i)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes() {
+        assert_eq!(Color::RED.fg_escape(ColorMode::Ansi16), "\x1b[31m");
+        assert_eq!(Color::Ansi(9).fg_escape(ColorMode::Ansi16), "\x1b[91m");
+        assert_eq!(
+            Color::Ansi256(196).fg_escape(ColorMode::Ansi256),
+            "\x1b[38;5;196m"
+        );
+        assert_eq!(
+            Color::Rgb(1, 2, 3).fg_escape(ColorMode::TrueColor),
+            "\x1b[38;2;1;2;3m"
+        );
+    }
+
+    #[test]
+    fn rgb_to_palette() {
+        assert_eq!(Color::Rgb(255, 0, 0).to_ansi256(), 196);
+        assert_eq!(Color::Rgb(128, 128, 128).to_ansi256(), 244);
+        assert_eq!(Color::Rgb(250, 10, 10).to_ansi16(), 9);
+        assert_eq!(Color::Ansi256(196).to_rgb(), (255, 0, 0));
+    }
+}
@@ -0,0 +1,401 @@
+use crate::shape::STEP;
+use crate::BoundingBox;
+use crate::Outline;
+use crate::Raster;
+use crate::Shape;
+use std::f32::consts::PI;
+
+/// the most segments an ellipse is sampled with, which keeps the chords of
+/// huge ellipses within a dot of the curve
+const MAX_SEGMENTS: f32 = 65536.0;
+
+/// An axis aligned ellipse centered at (x, y) with radius rx and ry
+pub struct Ellipse {
+    pub x: f32,
+    pub y: f32,
+    pub rx: f32,
+    pub ry: f32,
+}
+
+/// An elliptical arc from (x1, y1) to (x2, y2), which follows the semantics
+/// of the svg path `A` command
+pub struct EllipticalArc {
+    pub x1: f32,
+    pub y1: f32,
+    pub x2: f32,
+    pub y2: f32,
+    pub rx: f32,
+    pub ry: f32,
+    /// rotation of the x-axis of the ellipse in degrees
+    pub x_axis_rotation: f32,
+    /// take the arc that spans more than 180 degrees
+    pub large_arc_flag: bool,
+    /// draw the arc in the positive angle direction
+    pub sweep_flag: bool,
+}
+
+/// The center parameterization of an elliptical arc
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ArcCenter {
+    pub cx: f32,
+    pub cy: f32,
+    /// the x radius, scaled up if the given radius is too small
+    pub rx: f32,
+    /// the y radius, scaled up if the given radius is too small
+    pub ry: f32,
+    /// the start angle in radians
+    pub start_angle: f32,
+    /// the sweep angle in radians, negative when sweeping in the negative
+    /// angle direction
+    pub sweep_angle: f32,
+}
+
+impl Ellipse {
+    pub fn new(x: f32, y: f32, rx: f32, ry: f32) -> Self {
+        Ellipse { x, y, rx, ry }
+    }
+}
+
+impl EllipticalArc {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        x1: f32,
+        y1: f32,
+        x2: f32,
+        y2: f32,
+        rx: f32,
+        ry: f32,
+        x_axis_rotation: f32,
+        large_arc_flag: bool,
+        sweep_flag: bool,
+    ) -> Self {
+        EllipticalArc {
+            x1,
+            y1,
+            x2,
+            y2,
+            rx,
+            ry,
+            x_axis_rotation,
+            large_arc_flag,
+            sweep_flag,
+        }
+    }
+
+    /// Convert the endpoint parameterization into center parameterization
+    /// as described in the svg spec, appendix F.6.5.
+    /// Radii that are too small to span the endpoints are scaled up as in
+    /// F.6.6.
+    /// Returns None when the arc degenerates into a straight line or is
+    /// omitted entirely, which is when either radius is zero or both
+    /// endpoints are the same.
+    pub fn center(&self) -> Option<ArcCenter> {
+        let mut rx = self.rx.abs();
+        let mut ry = self.ry.abs();
+        if (self.x1 == self.x2 && self.y1 == self.y2) || rx == 0.0 || ry == 0.0
+        {
+            return None;
+        }
+        let phi = self.x_axis_rotation.to_radians();
+        let (sin_phi, cos_phi) = phi.sin_cos();
+        let dx2 = (self.x1 - self.x2) / 2.0;
+        let dy2 = (self.y1 - self.y2) / 2.0;
+        let x1p = cos_phi * dx2 + sin_phi * dy2;
+        let y1p = -sin_phi * dx2 + cos_phi * dy2;
+
+        let lambda = (x1p * x1p) / (rx * rx) + (y1p * y1p) / (ry * ry);
+        if lambda > 1.0 {
+            rx *= lambda.sqrt();
+            ry *= lambda.sqrt();
+        }
+
+        let num = rx * rx * ry * ry - rx * rx * y1p * y1p - ry * ry * x1p * x1p;
+        let den = rx * rx * y1p * y1p + ry * ry * x1p * x1p;
+        let sign = if self.large_arc_flag != self.sweep_flag {
+            1.0
+        } else {
+            -1.0
+        };
+        let coef = sign * (num / den).max(0.0).sqrt();
+        let cxp = coef * rx * y1p / ry;
+        let cyp = -coef * ry * x1p / rx;
+
+        let cx = cos_phi * cxp - sin_phi * cyp + (self.x1 + self.x2) / 2.0;
+        let cy = sin_phi * cxp + cos_phi * cyp + (self.y1 + self.y2) / 2.0;
+
+        let start_angle = ((y1p - cyp) / ry).atan2((x1p - cxp) / rx);
+        let end_angle = ((-y1p - cyp) / ry).atan2((-x1p - cxp) / rx);
+        let mut sweep_angle = (end_angle - start_angle) % (2.0 * PI);
+        if !self.sweep_flag && sweep_angle > 0.0 {
+            sweep_angle -= 2.0 * PI;
+        } else if self.sweep_flag && sweep_angle < 0.0 {
+            sweep_angle += 2.0 * PI;
+        }
+        Some(ArcCenter {
+            cx,
+            cy,
+            rx,
+            ry,
+            start_angle,
+            sweep_angle,
+        })
+    }
+}
+
+/// Sample the points of an ellipse rotated by `phi` radians, from angle
+/// `start` sweeping through `sweep` radians, close enough that
+/// consecutive points are at most `step` apart, with at most
+/// `MAX_SEGMENTS` segments.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn sample_ellipse(
+    cx: f32,
+    cy: f32,
+    rx: f32,
+    ry: f32,
+    phi: f32,
+    start: f32,
+    sweep: f32,
+    step: f32,
+) -> Vec<(f32, f32)> {
+    let (sin_phi, cos_phi) = phi.sin_cos();
+    let segments = (sweep.abs() * rx.max(ry) / step).ceil();
+    // also one segment when the count is not a number
+    let n = if segments > 1.0 {
+        segments.min(MAX_SEGMENTS) as usize
+    } else {
+        1
+    };
+    (0..=n)
+        .map(|i| {
+            let t = start + sweep * i as f32 / n as f32;
+            let (sin_t, cos_t) = t.sin_cos();
+            (
+                cx + rx * cos_t * cos_phi - ry * sin_t * sin_phi,
+                cy + rx * cos_t * sin_phi + ry * sin_t * cos_phi,
+            )
+        })
+        .collect()
+}
+
+impl<'a> Shape<'a> for Ellipse {
+    fn points(&'a self) -> Box<dyn Iterator<Item = (f32, f32)> + 'a> {
+        self.points_at_resolution(STEP)
+    }
+
+    fn points_at_resolution(
+        &'a self,
+        dot_size: f32,
+    ) -> Box<dyn Iterator<Item = (f32, f32)> + 'a> {
+        let points = sample_ellipse(
+            self.x,
+            self.y,
+            self.rx.abs(),
+            self.ry.abs(),
+            0.0,
+            0.0,
+            2.0 * PI,
+            dot_size,
+        );
+        Box::new(points.into_iter())
+    }
+
+    fn outlines(&'a self, dot_size: f32) -> Vec<Outline> {
+        let mut vertices: Vec<(f32, f32)> =
+            self.points_at_resolution(dot_size).collect();
+        // the last point is the same as the first point
+        vertices.pop();
+        vec![Outline::closed(vertices)]
+    }
+
+    fn rasterize(&'a self, raster: &mut Raster) {
+        raster.ellipse(self.x, self.y, self.rx, self.ry);
+    }
+
+    fn bounding_box(&'a self) -> Option<BoundingBox> {
+        let (rx, ry) = (self.rx.abs(), self.ry.abs());
+        Some(BoundingBox::new(
+            self.x - rx,
+            self.y - ry,
+            self.x + rx,
+            self.y + ry,
+        ))
+    }
+
+    /// an ellipse with a zero radius has no area, and contains no points
+    fn contains(&'a self, x: f32, y: f32) -> bool {
+        let (rx, ry) = (self.rx.abs(), self.ry.abs());
+        if rx == 0.0 || ry == 0.0 {
+            return false;
+        }
+        let dx = (x - self.x) / rx;
+        let dy = (y - self.y) / ry;
+        dx * dx + dy * dy <= 1.0
+    }
+}
+
+impl<'a> Shape<'a> for EllipticalArc {
+    fn points(&'a self) -> Box<dyn Iterator<Item = (f32, f32)> + 'a> {
+        self.points_at_resolution(STEP)
+    }
+
+    fn points_at_resolution(
+        &'a self,
+        dot_size: f32,
+    ) -> Box<dyn Iterator<Item = (f32, f32)> + 'a> {
+        match self.center() {
+            Some(c) => Box::new(
+                sample_ellipse(
+                    c.cx,
+                    c.cy,
+                    c.rx,
+                    c.ry,
+                    self.x_axis_rotation.to_radians(),
+                    c.start_angle,
+                    c.sweep_angle,
+                    dot_size,
+                )
+                .into_iter(),
+            ),
+            None if self.x1 == self.x2 && self.y1 == self.y2 => {
+                Box::new(std::iter::empty())
+            }
+            None => {
+                let line = crate::Line::new(self.x1, self.y1, self.x2, self.y2);
+                Box::new(
+                    line.iter_with_step(dot_size)
+                        .chain(Some((self.x2, self.y2))),
+                )
+            }
+        }
+    }
+
+    fn outlines(&'a self, dot_size: f32) -> Vec<Outline> {
+        match self.center() {
+            Some(_) => {
+                vec![Outline::open(
+                    self.points_at_resolution(dot_size).collect(),
+                )]
+            }
+            None if self.x1 == self.x2 && self.y1 == self.y2 => vec![],
+            None => vec![Outline::open(vec![
+                (self.x1, self.y1),
+                (self.x2, self.y2),
+            ])],
+        }
+    }
+
+    fn rasterize(&'a self, raster: &mut Raster) {
+        match self.center() {
+            Some(c) if self.x_axis_rotation.rem_euclid(180.0) == 0.0 => raster
+                .elliptical_arc(
+                    c.cx,
+                    c.cy,
+                    c.rx,
+                    c.ry,
+                    c.start_angle,
+                    c.sweep_angle,
+                ),
+            _ => {
+                for outline in self.outlines(raster.dot_size()) {
+                    raster.polyline(&outline.vertices, outline.closed);
+                }
+            }
+        }
+    }
+
+    /// the box around the circle of the largest radius of the ellipse,
+    /// which contains the arc at any rotation
+    fn bounding_box(&'a self) -> Option<BoundingBox> {
+        match self.center() {
+            Some(c) => {
+                let r = c.rx.max(c.ry);
+                Some(BoundingBox::new(c.cx - r, c.cy - r, c.cx + r, c.cy + r))
+            }
+            None => BoundingBox::from_points(vec![
+                (self.x1, self.y1),
+                (self.x2, self.y2),
+            ]),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    fn assert_close(a: f32, b: f32) {
+        assert!((a - b).abs() < 1e-4, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn sampling_is_bounded() {
+        let huge = sample_ellipse(0.0, 0.0, 1e30, 1e30, 0.0, 0.0, PI, 0.25);
+        assert_eq!(huge.len(), MAX_SEGMENTS as usize + 1);
+        let infinite =
+            sample_ellipse(0.0, 0.0, f32::INFINITY, 1.0, 0.0, 0.0, PI, 0.25);
+        assert_eq!(infinite.len(), MAX_SEGMENTS as usize + 1);
+        let tiny_step = sample_ellipse(0.0, 0.0, 1.0, 1.0, 0.0, 0.0, PI, 0.0);
+        assert_eq!(tiny_step.len(), MAX_SEGMENTS as usize + 1);
+    }
+
+    #[test]
+    fn contains_with_negative_and_zero_radius() {
+        let ellipse = Ellipse::new(0.0, 0.0, -2.0, -1.0);
+        assert!(ellipse.contains(1.5, 0.0));
+        assert!(!ellipse.contains(0.0, 1.5));
+        let flat = Ellipse::new(0.0, 0.0, 2.0, 0.0);
+        assert!(!flat.contains(0.0, 0.0));
+        assert!(!flat.contains(1.0, 0.0));
+    }
+
+    #[test]
+    fn arc_center_follows_flags() {
+        let arc = EllipticalArc::new(
+            0.0, 0.0, 10.0, 10.0, 10.0, 10.0, 0.0, false, false,
+        );
+        let c = arc.center().unwrap();
+        assert_close(c.cx, 10.0);
+        assert_close(c.cy, 0.0);
+        assert_close(c.start_angle, PI);
+        assert_close(c.sweep_angle, -PI / 2.0);
+
+        let arc = EllipticalArc::new(
+            0.0, 0.0, 10.0, 10.0, 10.0, 10.0, 0.0, true, false,
+        );
+        let c = arc.center().unwrap();
+        assert_close(c.cx, 0.0);
+        assert_close(c.cy, 10.0);
+        assert_close(c.sweep_angle, -3.0 * PI / 2.0);
+    }
+
+    #[test]
+    fn small_radius_is_scaled_up() {
+        let arc =
+            EllipticalArc::new(0.0, 0.0, 10.0, 0.0, 1.0, 2.0, 0.0, false, true);
+        let c = arc.center().unwrap();
+        assert_close(c.cx, 5.0);
+        assert_close(c.cy, 0.0);
+        assert_close(c.rx, 5.0);
+        assert_close(c.ry, 10.0);
+        assert_close(c.sweep_angle, PI);
+    }
+
+    #[test]
+    fn zero_radius_is_a_line() {
+        let arc =
+            EllipticalArc::new(0.0, 0.0, 1.0, 0.0, 0.0, 2.0, 0.0, false, true);
+        assert!(arc.center().is_none());
+        assert_eq!(arc.points().last(), Some((1.0, 0.0)));
+    }
+
+    #[test]
+    fn draw_ellipse() {
+        let mut context = Context::new(6.0, 3.0);
+        context.draw(&Ellipse::new(3.0, 1.5, 2.5, 1.25));
+        let result = context.to_string();
+        println!("{}", result);
+        let expected = [" ⢀⠤⠒⠒⠒⠒⠒⠒⠤⡀ ", " ⡇        ⢸ ", " ⠈⠒⠤⠤⠤⠤⠤⠤⠒⠁ "];
+        assert_eq!(result, expected.join("\n"));
+    }
+}
@@ -0,0 +1,121 @@
+/// ```ignore
+///      0 1 2 3 4           B C D          BRAILLE ⣿
+///     0┌─┬─┬─┬─┐        A┌─┬─┬─┬─┐E       ┌       ┐
+///     1├─┼─┼─┼─┤         │ │ │ │ │         ─▮───▮─
+///     2├─┼─┼─┼─┤        F├─G─H─I─┤J         │   │
+///     3├─┼─┼─┼─┤         │ │ │ │ │         ─▮───▮─
+///     4├─┼─┼─┼─┤        K├─L─M─N─┤O         │   │
+///     5├─┼─┼─┼─┤         │ │ │ │ │         ─▮───▮─
+///     6├─┼─┼─┼─┤        P├─Q─R─S─┤T         │   │
+///     7├─┼─┼─┼─┤         │ │ │ │ │         ─▮───▮─
+///     8└─┴─┴─┴─┘        U└─┴─┴─┴─┘Y       └       ┘
+/// ```                      V W X
+pub const DOTS: [[u16; 2]; 4] = [
+    [0x0001, 0x0008],
+    [0x0002, 0x0010],
+    [0x0004, 0x0020],
+    [0x0040, 0x0080],
+];
+pub const BRAILLE_OFFSET: u16 = 0x2800;
+
+/// the first character of the unicode 13 sextant block
+const SEXTANT_OFFSET: u32 = 0x1FB00;
+
+const HALF_BLOCKS: [char; 4] = [' ', '▀', '▄', '█'];
+
+/// indexed by the top left, top right, bottom left and bottom right bits
+const QUADRANTS: [char; 16] = [
+    ' ', '▘', '▝', '▀', '▖', '▌', '▞', '▛', '▗', '▚', '▐', '▜', '▄', '▙', '▟',
+    '█',
+];
+
+const ASCII: [char; 4] = [' ', '\'', '.', ':'];
+
+/// The characters used to draw the dots of a cell.
+/// The dots of a cell are stored as bits, where bit `row * width + column`
+/// is the dot at that column and row of the cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Encoding {
+    /// braille patterns, 2 dots wide and 4 dots high
+    #[default]
+    Braille,
+    /// the upper and lower half blocks `▀▄█`, 1 dot wide and 2 dots high
+    HalfBlock,
+    /// the quadrant blocks `▘▝▖▗▚▞`.., 2 dots wide and 2 dots high
+    Quadrant,
+    /// the unicode 13 sextant blocks, 2 dots wide and 3 dots high
+    Sextant,
+    /// the ascii characters `'.:`, 1 dot wide and 2 dots high
+    Ascii,
+}
+
+impl Encoding {
+    /// the number of dots of a cell horizontally and vertically
+    pub fn cell_size(&self) -> (usize, usize) {
+        match self {
+            Encoding::Braille => (2, 4),
+            Encoding::HalfBlock => (1, 2),
+            Encoding::Quadrant => (2, 2),
+            Encoding::Sextant => (2, 3),
+            Encoding::Ascii => (1, 2),
+        }
+    }
+
+    /// the character of a cell with the dots set, an empty cell is a space
+    pub fn to_char(&self, dots: u8) -> char {
+        match self {
+            Encoding::Braille => {
+                let mut ch = BRAILLE_OFFSET;
+                for (row, row_dots) in DOTS.iter().enumerate() {
+                    for (column, dot) in row_dots.iter().enumerate() {
+                        if dots & (1 << (row * 2 + column)) != 0 {
+                            ch |= dot;
+                        }
+                    }
+                }
+                if ch == BRAILLE_OFFSET {
+                    ' '
+                } else {
+                    char::from_u32(ch as u32).unwrap()
+                }
+            }
+            Encoding::HalfBlock => HALF_BLOCKS[(dots & 0b11) as usize],
+            Encoding::Quadrant => QUADRANTS[(dots & 0b1111) as usize],
+            Encoding::Sextant => match dots & 0b11_1111 {
+                0 => ' ',
+                // the left column, right column and full block are not in
+                // the sextant block, as they already exist
+                0b01_0101 => '▌',
+                0b10_1010 => '▐',
+                0b11_1111 => '█',
+                n => {
+                    let n = n as u32;
+                    let skipped =
+                        (n > 0b01_0101) as u32 + (n > 0b10_1010) as u32;
+                    char::from_u32(SEXTANT_OFFSET + n - 1 - skipped).unwrap()
+                }
+            },
+            Encoding::Ascii => ASCII[(dots & 0b11) as usize],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn characters() {
+        assert_eq!(Encoding::Braille.to_char(0), ' ');
+        assert_eq!(Encoding::Braille.to_char(0b1111_1111), '⣿');
+        assert_eq!(Encoding::Braille.to_char(0b0100_0010), '⡈');
+        assert_eq!(Encoding::HalfBlock.to_char(0b10), '▄');
+        assert_eq!(Encoding::Quadrant.to_char(0b1001), '▚');
+        assert_eq!(Encoding::Sextant.to_char(0b00_0001), '\u{1FB00}');
+        assert_eq!(Encoding::Sextant.to_char(0b01_0110), '\u{1FB14}');
+        assert_eq!(Encoding::Sextant.to_char(0b10_1011), '\u{1FB28}');
+        assert_eq!(Encoding::Sextant.to_char(0b11_1110), '\u{1FB3B}');
+        assert_eq!(Encoding::Sextant.to_char(0b10_1010), '▐');
+        assert_eq!(Encoding::Ascii.to_char(0b11), ':');
+    }
+}
@@ -0,0 +1,249 @@
+//! Export of the dots of a `Grid` as PBM, PNG and SVG images, with one
+//! pixel per dot.
+use crate::Grid;
+use std::io;
+use std::io::Write;
+
+/// The shape of each dot in the exported svg
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SvgDot {
+    Circle,
+    Square,
+}
+
+/// the png file signature
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+
+/// the maximum length of a stored deflate block
+const MAX_STORED_BLOCK: usize = 65535;
+
+impl Grid {
+    /// the rows of dots packed into bytes, most significant bit first, where
+    /// the bit is `set` for lit dots
+    fn packed_rows(&self, set: bool) -> Vec<Vec<u8>> {
+        let (dots_wide, dots_high) = self.resolution();
+        (0..dots_high)
+            .map(|dy| {
+                let mut row =
+                    vec![if set { 0 } else { 0xff }; dots_wide.div_ceil(8)];
+                for dx in 0..dots_wide {
+                    if self.get_dot(dx, dy) {
+                        let bit = 0x80 >> (dx % 8);
+                        if set {
+                            row[dx / 8] |= bit;
+                        } else {
+                            row[dx / 8] &= !bit;
+                        }
+                    }
+                }
+                row
+            })
+            .collect()
+    }
+
+    /// Write the dots as a binary PBM image, where the lit dots are black
+    pub fn write_pbm<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let (dots_wide, dots_high) = self.resolution();
+        write!(writer, "P4\n{} {}\n", dots_wide, dots_high)?;
+        for row in self.packed_rows(true) {
+            writer.write_all(&row)?;
+        }
+        Ok(())
+    }
+
+    /// Write the dots as a 1 bit grayscale PNG image, where the lit dots are
+    /// black. A grid without any dots is an error, as a png has at least
+    /// one pixel.
+    pub fn write_png<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let (dots_wide, dots_high) = self.resolution();
+        if dots_wide == 0 || dots_high == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "png images can't be empty",
+            ));
+        }
+        writer.write_all(&PNG_SIGNATURE)?;
+
+        let mut header = vec![];
+        header.extend_from_slice(&(dots_wide as u32).to_be_bytes());
+        header.extend_from_slice(&(dots_high as u32).to_be_bytes());
+        // bit depth 1, grayscale, deflate, adaptive filtering, no interlace
+        header.extend_from_slice(&[1, 0, 0, 0, 0]);
+        write_chunk(writer, b"IHDR", &header)?;
+
+        // each row starts with the filter type, which is none
+        let mut data = vec![];
+        for row in self.packed_rows(false) {
+            data.push(0);
+            data.extend_from_slice(&row);
+        }
+        write_chunk(writer, b"IDAT", &zlib_stored(&data))?;
+        write_chunk(writer, b"IEND", &[])
+    }
+
+    /// Write the dots as an svg document with a circle or square for each
+    /// lit dot, in the color of its cell
+    pub fn write_svg<W: Write>(
+        &self,
+        writer: &mut W,
+        shape: SvgDot,
+    ) -> io::Result<()> {
+        let (dots_wide, dots_high) = self.resolution();
+        let (cell_width, cell_height) = self.encoding().cell_size();
+        writeln!(
+            writer,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}">"#,
+            dots_wide, dots_high
+        )?;
+        writeln!(writer, r#"<g fill="black">"#)?;
+        for dy in 0..dots_high {
+            for dx in 0..dots_wide {
+                if !self.get_dot(dx, dy) {
+                    continue;
+                }
+                let fill = match self.color(dx / cell_width, dy / cell_height) {
+                    Some(color) => {
+                        let (r, g, b) = color.to_rgb();
+                        format!(r##" fill="#{:02x}{:02x}{:02x}""##, r, g, b)
+                    }
+                    None => String::new(),
+                };
+                match shape {
+                    SvgDot::Circle => writeln!(
+                        writer,
+                        r#"<circle cx="{}.5" cy="{}.5" r="0.5"{}/>"#,
+                        dx, dy, fill
+                    )?,
+                    SvgDot::Square => writeln!(
+                        writer,
+                        r#"<rect x="{}" y="{}" width="1" height="1"{}/>"#,
+                        dx, dy, fill
+                    )?,
+                }
+            }
+        }
+        writeln!(writer, "</g>")?;
+        writeln!(writer, "</svg>")
+    }
+}
+
+fn write_chunk<W: Write>(
+    writer: &mut W,
+    kind: &[u8; 4],
+    data: &[u8],
+) -> io::Result<()> {
+    writer.write_all(&(data.len() as u32).to_be_bytes())?;
+    writer.write_all(kind)?;
+    writer.write_all(data)?;
+    let crc = crc32(&[&kind[..], data].concat());
+    writer.write_all(&crc.to_be_bytes())
+}
+
+/// the data in a zlib stream of uncompressed deflate blocks
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    // deflate with a 32k window and the fastest compression level
+    let mut out = vec![0x78, 0x01];
+    let mut blocks = data.chunks(MAX_STORED_BLOCK).peekable();
+    if blocks.peek().is_none() {
+        out.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none();
+        out.push(last as u8);
+        let len = block.len() as u16;
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&(!len).to_le_bytes());
+        out.extend_from_slice(block);
+    }
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xffff_ffffu32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xedb8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for byte in data {
+        a = (a + *byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    b << 16 | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    fn context() -> Context {
+        let mut context = Context::new(1.0, 1.0);
+        context.draw(&Line::new(0.0, 0.0, 1.0, 1.0));
+        context.draw_with_color(&Dot { x: 0.0, y: 0.75 }, Color::RED);
+        context
+    }
+
+    #[test]
+    fn checksums() {
+        assert_eq!(crc32(b"IEND"), 0xae42_6082);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+    }
+
+    #[test]
+    fn export_pbm() {
+        let mut pbm = vec![];
+        context().grid().write_pbm(&mut pbm).unwrap();
+        let expected = b"P4\n4 4\n\x80\x40\x20\x90";
+        assert_eq!(pbm, expected);
+        let image = Image::parse(&pbm).unwrap();
+        assert_eq!((image.width, image.height), (4, 4));
+    }
+
+    #[test]
+    fn export_png() {
+        let mut png = vec![];
+        context().grid().write_png(&mut png).unwrap();
+        assert_eq!(png[..8], PNG_SIGNATURE);
+        assert_eq!(&png[12..16], b"IHDR");
+        assert_eq!(png[16..24], [0, 0, 0, 4, 0, 0, 0, 4]);
+        assert_eq!(&png[png.len() - 8..png.len() - 4], b"IEND");
+        let idat = png.windows(4).position(|w| w == b"IDAT").unwrap();
+        // the rows after the zlib and stored block headers
+        let rows = &png[idat + 4 + 7..idat + 4 + 7 + 8];
+        assert_eq!(rows, [0, 0x7f, 0, 0xbf, 0, 0xdf, 0, 0x6f]);
+    }
+
+    #[test]
+    fn export_empty_png() {
+        let mut png = vec![];
+        let err = Grid::new(0, 0).write_png(&mut png).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert!(png.is_empty());
+    }
+
+    #[test]
+    fn export_svg() {
+        let mut svg = vec![];
+        context()
+            .grid()
+            .write_svg(&mut svg, SvgDot::Square)
+            .unwrap();
+        let svg = String::from_utf8(svg).unwrap();
+        println!("{}", svg);
+        assert!(svg.contains(r#"<rect x="3" y="3" width="1" height="1"/>"#));
+        assert!(svg.contains(
+            r##"<rect x="0" y="3" width="1" height="1" fill="#800000"/>"##
+        ));
+        let parsed = Svg::parse(&svg).unwrap();
+        assert_eq!(parsed.elements.len(), 5);
+    }
+}
@@ -0,0 +1,640 @@
+use crate::ellipse::sample_ellipse;
+use crate::shape::outline_distance;
+use crate::shape::valid_step;
+use crate::shape::STEP;
+use crate::BoundingBox;
+use crate::Color;
+use crate::Grid;
+use crate::Outline;
+use crate::Raster;
+use crate::Shape;
+use std::f32::consts::PI;
+
+/// Determines which regions of a self-intersecting polygon are inside
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum FillRule {
+    /// a point is inside if a ray from it crosses the outline an odd number of times
+    EvenOdd,
+    /// a point is inside if the outline winds around it a non-zero number of times
+    #[default]
+    NonZero,
+}
+
+/// Which neighbors of a dot are part of the same region in a flood fill
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Connectivity {
+    /// the dots to the left, right, top and bottom
+    #[default]
+    Four,
+    /// the dots to the sides and the diagonals, which leaks through
+    /// diagonal lines
+    Eight,
+}
+
+/// A polygon where every dot inside the outline is set
+pub struct FilledPolygon {
+    pub points: Vec<(f32, f32)>,
+    pub fill_rule: FillRule,
+}
+
+/// A rectangle where every dot inside is set
+pub struct FilledRectangle {
+    pub x1: f32,
+    pub y1: f32,
+    pub x2: f32,
+    pub y2: f32,
+}
+
+/// A solid disc
+pub struct FilledCircle {
+    pub x: f32,
+    pub y: f32,
+    pub radius: f32,
+}
+
+impl FilledPolygon {
+    pub fn new(points: &[(f32, f32)], fill_rule: FillRule) -> Self {
+        FilledPolygon {
+            points: points.to_vec(),
+            fill_rule,
+        }
+    }
+}
+
+impl FilledRectangle {
+    pub fn new(x1: f32, y1: f32, x2: f32, y2: f32) -> Self {
+        FilledRectangle { x1, y1, x2, y2 }
+    }
+
+    /// the center of each dot inside the rectangle and the clip
+    fn points_within(
+        &self,
+        dot_size: f32,
+        clip: BoundingBox,
+    ) -> impl Iterator<Item = (f32, f32)> {
+        let (x1, x2) = (self.x1.min(self.x2), self.x1.max(self.x2));
+        scanlines(self.y1, self.y2, dot_size, (clip.min_y, clip.max_y))
+            .flat_map(move |y| span(x1, x2, y, dot_size, clip))
+    }
+}
+
+impl FilledCircle {
+    pub fn new(x: f32, y: f32, radius: f32) -> Self {
+        FilledCircle { x, y, radius }
+    }
+
+    /// the center of each dot inside the circle and the clip
+    fn points_within(
+        &self,
+        dot_size: f32,
+        clip: BoundingBox,
+    ) -> impl Iterator<Item = (f32, f32)> {
+        let (cx, cy, r) = (self.x, self.y, self.radius.abs());
+        scanlines(cy - r, cy + r, dot_size, (clip.min_y, clip.max_y)).flat_map(
+            move |y| {
+                let half = (r * r - (y - cy).powi(2)).max(0.0).sqrt();
+                span(cx - half, cx + half, y, dot_size, clip)
+            },
+        )
+    }
+}
+
+/// The y of the center of each row of dots in between y1 and y2, where
+/// the dots are `step` high, limited to the rows in between min and max
+fn scanlines(
+    y1: f32,
+    y2: f32,
+    step: f32,
+    (min, max): (f32, f32),
+) -> impl Iterator<Item = f32> {
+    let step = valid_step(step);
+    let (low, high) = (y1.min(y2).max(min), y1.max(y2).min(max));
+    let (start, end) = if low.is_finite() && high.is_finite() && low < high {
+        (
+            (low / step - 0.5).ceil() as i32,
+            (high / step - 0.5).ceil() as i32,
+        )
+    } else {
+        (0, 0)
+    };
+    (start..end).map(move |row| (row as f32 + 0.5) * step)
+}
+
+/// the points at the center of every dot in the span x1..x2 of row y,
+/// limited to the clip
+fn span(
+    x1: f32,
+    x2: f32,
+    y: f32,
+    step: f32,
+    clip: BoundingBox,
+) -> impl Iterator<Item = (f32, f32)> {
+    scanlines(x1, x2, step, (clip.min_x, clip.max_x)).map(move |x| (x, y))
+}
+
+/// Rasterize the closed rings with the scanline algorithm, returning the
+/// center point of each dot that is inside the rings and the clip.
+/// Multiple rings are filled as one shape, so holes can be expressed with
+/// the fill rule.
+pub(crate) fn scanline_fill(
+    rings: &[Vec<(f32, f32)>],
+    fill_rule: FillRule,
+    step: f32,
+    clip: BoundingBox,
+) -> Vec<(f32, f32)> {
+    let edges: Vec<((f32, f32), (f32, f32))> = rings
+        .iter()
+        .filter(|ring| ring.len() > 2)
+        .flat_map(|ring| {
+            ring.iter()
+                .zip(ring.iter().cycle().skip(1))
+                .map(|(p0, p1)| (*p0, *p1))
+        })
+        // edges with a vertex that is not finite are left out
+        .filter(|((x0, y0), (x1, y1))| {
+            [x0, y0, x1, y1].iter().all(|v| v.is_finite())
+        })
+        .filter(|(p0, p1)| p0.1 != p1.1)
+        .collect();
+
+    let min_y = edges
+        .iter()
+        .map(|(p0, p1)| p0.1.min(p1.1))
+        .fold(f32::INFINITY, f32::min);
+    let max_y = edges
+        .iter()
+        .map(|(p0, p1)| p0.1.max(p1.1))
+        .fold(f32::NEG_INFINITY, f32::max);
+
+    let mut points = vec![];
+    if edges.is_empty() {
+        return points;
+    }
+    for y in scanlines(min_y, max_y, step, (clip.min_y, clip.max_y)) {
+        // the x of each edge crossing this scanline and its winding direction
+        let mut crossings: Vec<(f32, i32)> = edges
+            .iter()
+            .filter(|(p0, p1)| y >= p0.1.min(p1.1) && y < p0.1.max(p1.1))
+            .map(|((x0, y0), (x1, y1))| {
+                let x = x0 + (y - y0) * (x1 - x0) / (y1 - y0);
+                let winding = if y1 > y0 { 1 } else { -1 };
+                (x, winding)
+            })
+            .collect();
+        crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let mut winding = 0;
+        for pair in crossings.windows(2) {
+            let (x1, w) = pair[0];
+            let (x2, _) = pair[1];
+            winding += w;
+            let inside = match fill_rule {
+                FillRule::EvenOdd => winding % 2 != 0,
+                FillRule::NonZero => winding != 0,
+            };
+            if inside {
+                points.extend(span(x1, x2, y, step, clip));
+            }
+        }
+    }
+    points
+}
+
+impl<'a> Shape<'a> for FilledPolygon {
+    fn points(&'a self) -> Box<dyn Iterator<Item = (f32, f32)> + 'a> {
+        self.points_at_resolution(STEP)
+    }
+
+    fn points_at_resolution(
+        &'a self,
+        dot_size: f32,
+    ) -> Box<dyn Iterator<Item = (f32, f32)> + 'a> {
+        let rings = std::slice::from_ref(&self.points);
+        let points = scanline_fill(
+            rings,
+            self.fill_rule,
+            dot_size,
+            BoundingBox::UNBOUNDED,
+        );
+        Box::new(points.into_iter())
+    }
+
+    /// the boundary, which is stroked when the stroke is wider than a dot
+    fn outlines(&'a self, _dot_size: f32) -> Vec<Outline> {
+        vec![Outline::closed(self.points.clone())]
+    }
+
+    /// the area is set by the fill, the boundary is only drawn for strokes
+    /// wider than a dot
+    fn rasterize(&'a self, _raster: &mut Raster) {}
+
+    fn fill(&'a self, raster: &mut Raster) {
+        let rings = std::slice::from_ref(&self.points);
+        let clip = raster.visible_bounds();
+        for point in
+            scanline_fill(rings, self.fill_rule, raster.dot_size(), clip)
+        {
+            raster.point(point);
+        }
+    }
+
+    fn bounding_box(&'a self) -> Option<BoundingBox> {
+        BoundingBox::from_points(self.points.iter().copied())
+    }
+
+    /// zero inside the area, else the distance to the boundary
+    fn distance(&'a self, x: f32, y: f32) -> f32 {
+        if self.contains(x, y) {
+            return 0.0;
+        }
+        outline_distance(&Outline::closed(self.points.clone()), (x, y))
+    }
+
+    fn contains(&'a self, x: f32, y: f32) -> bool {
+        ring_contains(&self.points, self.fill_rule, (x, y))
+    }
+}
+
+impl<'a> Shape<'a> for FilledRectangle {
+    fn points(&'a self) -> Box<dyn Iterator<Item = (f32, f32)> + 'a> {
+        self.points_at_resolution(STEP)
+    }
+
+    fn points_at_resolution(
+        &'a self,
+        dot_size: f32,
+    ) -> Box<dyn Iterator<Item = (f32, f32)> + 'a> {
+        Box::new(self.points_within(dot_size, BoundingBox::UNBOUNDED))
+    }
+
+    /// the boundary, which is stroked when the stroke is wider than a dot
+    fn outlines(&'a self, _dot_size: f32) -> Vec<Outline> {
+        let corners = [
+            (self.x1, self.y1),
+            (self.x2, self.y1),
+            (self.x2, self.y2),
+            (self.x1, self.y2),
+        ];
+        vec![Outline::closed(corners.to_vec())]
+    }
+
+    /// the area is set by the fill, the boundary is only drawn for strokes
+    /// wider than a dot
+    fn rasterize(&'a self, _raster: &mut Raster) {}
+
+    fn fill(&'a self, raster: &mut Raster) {
+        let clip = raster.visible_bounds();
+        for point in self.points_within(raster.dot_size(), clip) {
+            raster.point(point);
+        }
+    }
+
+    fn bounding_box(&'a self) -> Option<BoundingBox> {
+        BoundingBox::from_points(vec![(self.x1, self.y1), (self.x2, self.y2)])
+    }
+
+    /// zero inside the area, else the distance to the boundary
+    fn distance(&'a self, x: f32, y: f32) -> f32 {
+        match self.bounding_box() {
+            Some(b) => {
+                let dx = (b.min_x - x).max(x - b.max_x).max(0.0);
+                let dy = (b.min_y - y).max(y - b.max_y).max(0.0);
+                dx.hypot(dy)
+            }
+            None => f32::INFINITY,
+        }
+    }
+
+    fn contains(&'a self, x: f32, y: f32) -> bool {
+        self.bounding_box()
+            .is_some_and(|bounding_box| bounding_box.contains(x, y))
+    }
+}
+
+impl<'a> Shape<'a> for FilledCircle {
+    fn points(&'a self) -> Box<dyn Iterator<Item = (f32, f32)> + 'a> {
+        self.points_at_resolution(STEP)
+    }
+
+    fn points_at_resolution(
+        &'a self,
+        dot_size: f32,
+    ) -> Box<dyn Iterator<Item = (f32, f32)> + 'a> {
+        Box::new(self.points_within(dot_size, BoundingBox::UNBOUNDED))
+    }
+
+    /// the boundary, which is stroked when the stroke is wider than a dot
+    fn outlines(&'a self, dot_size: f32) -> Vec<Outline> {
+        let r = self.radius.abs();
+        let mut vertices =
+            sample_ellipse(self.x, self.y, r, r, 0.0, 0.0, 2.0 * PI, dot_size);
+        // the last point is the same as the first point
+        vertices.pop();
+        vec![Outline::closed(vertices)]
+    }
+
+    /// the area is set by the fill, the boundary is only drawn for strokes
+    /// wider than a dot
+    fn rasterize(&'a self, _raster: &mut Raster) {}
+
+    fn fill(&'a self, raster: &mut Raster) {
+        let clip = raster.visible_bounds();
+        for point in self.points_within(raster.dot_size(), clip) {
+            raster.point(point);
+        }
+    }
+
+    fn bounding_box(&'a self) -> Option<BoundingBox> {
+        let r = self.radius.abs();
+        Some(BoundingBox::new(
+            self.x - r,
+            self.y - r,
+            self.x + r,
+            self.y + r,
+        ))
+    }
+
+    /// zero inside the area, else the distance to the boundary
+    fn distance(&'a self, x: f32, y: f32) -> f32 {
+        let center = (x - self.x).hypot(y - self.y);
+        (center - self.radius.abs()).max(0.0)
+    }
+
+    fn contains(&'a self, x: f32, y: f32) -> bool {
+        (x - self.x).powi(2) + (y - self.y).powi(2) <= self.radius.powi(2)
+    }
+}
+
+/// The number of times the closed ring winds around the point, with the
+/// same edge rules as the scanline fill
+pub(crate) fn winding(ring: &[(f32, f32)], (x, y): (f32, f32)) -> i32 {
+    if ring.len() < 3 {
+        return 0;
+    }
+    let mut winding = 0;
+    for (p0, p1) in ring.iter().zip(ring.iter().cycle().skip(1)) {
+        let ((x0, y0), (x1, y1)) = (*p0, *p1);
+        if y0 == y1 || y < y0.min(y1) || y >= y0.max(y1) {
+            continue;
+        }
+        let cross_x = x0 + (y - y0) * (x1 - x0) / (y1 - y0);
+        if cross_x <= x {
+            winding += if y1 > y0 { 1 } else { -1 };
+        }
+    }
+    winding
+}
+
+/// Whether the point is inside the closed ring, with the same edge rules
+/// as the scanline fill
+pub(crate) fn ring_contains(
+    ring: &[(f32, f32)],
+    fill_rule: FillRule,
+    point: (f32, f32),
+) -> bool {
+    let winding = winding(ring, point);
+    match fill_rule {
+        FillRule::EvenOdd => winding % 2 != 0,
+        FillRule::NonZero => winding != 0,
+    }
+}
+
+impl Grid {
+    /// Light up the unlit region of dots connected to the dot at (dx, dy),
+    /// bounded by the lit dots and the edges of the grid.
+    /// The color of the cells is replaced when a color is specified.
+    /// Returns the number of dots filled, or None without filling anything
+    /// when the region is larger than `max_area` dots.
+    pub fn flood_fill(
+        &mut self,
+        dx: usize,
+        dy: usize,
+        connectivity: Connectivity,
+        max_area: Option<usize>,
+        color: Option<Color>,
+    ) -> Option<usize> {
+        let (dots_wide, dots_high) = self.resolution();
+        if dx >= dots_wide || dy >= dots_high || self.get_dot(dx, dy) {
+            return Some(0);
+        }
+        let neighbors: &[(isize, isize)] = match connectivity {
+            Connectivity::Four => &[(-1, 0), (1, 0), (0, -1), (0, 1)],
+            Connectivity::Eight => &[
+                (-1, 0),
+                (1, 0),
+                (0, -1),
+                (0, 1),
+                (-1, -1),
+                (1, -1),
+                (-1, 1),
+                (1, 1),
+            ],
+        };
+        let mut visited = vec![false; dots_wide * dots_high];
+        visited[dy * dots_wide + dx] = true;
+        let mut region = vec![];
+        let mut stack = vec![(dx, dy)];
+        while let Some((x, y)) = stack.pop() {
+            region.push((x, y));
+            if max_area.is_some_and(|max| region.len() > max) {
+                return None;
+            }
+            for (ox, oy) in neighbors {
+                let nx = x as isize + ox;
+                let ny = y as isize + oy;
+                if nx < 0
+                    || ny < 0
+                    || nx as usize >= dots_wide
+                    || ny as usize >= dots_high
+                {
+                    continue;
+                }
+                let (nx, ny) = (nx as usize, ny as usize);
+                let index = ny * dots_wide + nx;
+                if !visited[index] && !self.get_dot(nx, ny) {
+                    visited[index] = true;
+                    stack.push((nx, ny));
+                }
+            }
+        }
+        for (x, y) in region.iter() {
+            self.set_dot(*x, *y, color);
+        }
+        Some(region.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    /// a self intersecting 5 pointed star
+    fn star() -> Vec<(f32, f32)> {
+        vec![
+            (5.0, 0.0),
+            (8.0, 10.0),
+            (0.0, 4.0),
+            (10.0, 4.0),
+            (2.0, 10.0),
+        ]
+    }
+
+    #[test]
+    fn fill_rectangle() {
+        let mut context = Context::new(4.0, 2.0);
+        context.draw(&FilledRectangle::new(0.5, 0.0, 1.5, 1.0));
+        let result = context.to_string();
+        println!("{}", result);
+        let expected = [" ⣿⣿     ", "        "];
+        assert_eq!(result, expected.join("\n"));
+    }
+
+    #[test]
+    fn fill_rectangle_sets_each_dot_once() {
+        let rect = FilledRectangle::new(0.0, 0.0, 1.0, 1.0);
+        assert_eq!(rect.points().count(), 16);
+    }
+
+    #[test]
+    fn fill_circle() {
+        let mut context = Context::new(5.0, 5.0);
+        context.draw(&FilledCircle::new(2.5, 2.5, 2.0));
+        let result = context.to_string();
+        println!("{}", result);
+        let expected = [
+            "  ⢀⣠⣤⣤⣄⡀  ",
+            " ⣰⣿⣿⣿⣿⣿⣿⣆ ",
+            " ⣿⣿⣿⣿⣿⣿⣿⣿ ",
+            " ⠹⣿⣿⣿⣿⣿⣿⠏ ",
+            "  ⠈⠙⠛⠛⠋⠁  ",
+        ];
+        assert_eq!(result, expected.join("\n"));
+    }
+
+    #[test]
+    fn even_odd_leaves_star_center_empty() {
+        let polygon = FilledPolygon::new(&star(), FillRule::EvenOdd);
+        let center = (5.125, 5.625);
+        assert!(!polygon.points().any(|p| p == center));
+        assert!(polygon.points().any(|p| p == (5.125, 1.125)));
+    }
+
+    #[test]
+    fn non_zero_fills_star_center() {
+        let polygon = FilledPolygon::new(&star(), FillRule::NonZero);
+        let center = (5.125, 5.625);
+        assert!(polygon.points().any(|p| p == center));
+        assert!(polygon.points().any(|p| p == (5.125, 1.125)));
+    }
+
+    #[test]
+    fn flood_fill_inside_outline() {
+        let mut context = Context::new(3.0, 2.0);
+        context.draw(&Rectangle::new(0.0, 0.0, 2.75, 1.75));
+        context.draw(&Line::new(1.5, 0.0, 0.0, 1.5));
+        let mut four = context.grid().clone();
+        assert_eq!(
+            four.flood_fill(0, 0, Connectivity::Four, None, None),
+            Some(0)
+        );
+        let filled = four.flood_fill(8, 4, Connectivity::Four, None, None);
+        let result = four.to_string();
+        println!("{}", result);
+        let expected = ["⡏⣩⣿⣿⣿⣿", "⣿⣿⣿⣿⣿⣿"];
+        assert_eq!(result, expected.join("\n"));
+        assert_eq!(filled, Some(50));
+
+        let mut eight = context.grid().clone();
+        let filled = eight.flood_fill(8, 4, Connectivity::Eight, None, None);
+        let result = eight.to_string();
+        println!("{}", result);
+        let expected = ["⣿⣿⣿⣿⣿⣿", "⣿⣿⣿⣿⣿⣿"];
+        assert_eq!(result, expected.join("\n"));
+        assert_eq!(filled, Some(56));
+    }
+
+    #[test]
+    fn flood_fill_max_area() {
+        let mut grid = Grid::new(2, 1);
+        assert_eq!(
+            grid.flood_fill(0, 0, Connectivity::Four, Some(15), None),
+            None
+        );
+        assert_eq!(grid.to_string(), "  ");
+        let mut context = Context::new(1.0, 1.0);
+        context.draw(&Line::new(0.5, 0.0, 0.5, 1.0));
+        assert_eq!(
+            context.flood_fill_point(0.25, 0.5, Connectivity::Four, Some(8)),
+            Some(8)
+        );
+        assert_eq!(context.to_string(), "⣿⡇");
+    }
+
+    #[test]
+    fn non_finite_vertices_are_ignored() {
+        let polygon = FilledPolygon::new(
+            &[(0.0, 0.0), (f32::NAN, 1.0), (2.0, 2.0)],
+            FillRule::NonZero,
+        );
+        assert!(polygon
+            .points()
+            .all(|(x, y)| x.is_finite() && y.is_finite()));
+    }
+
+    #[test]
+    fn wide_stroke_outlines_the_fill() {
+        let circle = FilledCircle::new(1.0, 1.0, 0.5);
+        let outlines = circle.outlines(0.25);
+        assert_eq!(outlines.len(), 1);
+        assert!(outlines[0].closed);
+
+        let mut context = Context::new(2.0, 1.0);
+        context.set_stroke_style(StrokeStyle::new(2.0));
+        context.draw(&FilledRectangle::new(0.5, 0.25, 1.5, 0.75));
+        let result = context.to_string();
+        println!("{}", result);
+        let expected = ["⢸⣿⣿⡇"];
+        assert_eq!(result, expected.join("\n"));
+    }
+
+    #[test]
+    fn fill_is_clipped_to_the_canvas() {
+        let mut context = Context::new(2.0, 1.0);
+        context.draw(&FilledRectangle::new(-2000.0, -2000.0, 2000.0, 2000.0));
+        assert_eq!(context.to_string(), "⣿⣿⣿⣿");
+
+        let mut context = Context::new(2.0, 1.0);
+        context.zoom(1000.0);
+        context.draw(&FilledCircle::new(1.0, 0.5, 2.0));
+        assert_eq!(context.to_string(), "⣿⣿⣿⣿");
+
+        let infinite = f32::INFINITY;
+        let mut context = Context::new(2.0, 1.0);
+        context.draw(&FilledRectangle::new(-infinite, 0.5, infinite, infinite));
+        assert_eq!(context.to_string(), "⣤⣤⣤⣤");
+
+        let clip = BoundingBox::new(0.0, 0.0, 2.0, 1.0);
+        let huge = FilledRectangle::new(-1e6, -1e6, 1e6, 1e6);
+        assert_eq!(huge.points_within(0.25, clip).count(), 32);
+    }
+
+    #[test]
+    fn distance_is_zero_inside_the_area() {
+        let rect = FilledRectangle::new(0.0, 0.0, 2.0, 1.0);
+        assert_eq!(rect.distance(1.0, 0.5), 0.0);
+        assert_eq!(rect.distance(1.0, 3.0), 2.0);
+        assert_eq!(rect.distance(5.0, 5.0), 5.0);
+
+        let circle = FilledCircle::new(0.0, 0.0, 1.0);
+        assert_eq!(circle.distance(0.1, 0.1), 0.0);
+        assert_eq!(circle.distance(0.0, -3.0), 2.0);
+
+        let polygon = FilledPolygon::new(
+            &[(0.0, 0.0), (4.0, 0.0), (0.0, 4.0)],
+            FillRule::NonZero,
+        );
+        assert_eq!(polygon.distance(1.0, 1.0), 0.0);
+        assert_eq!(polygon.distance(-1.0, 2.0), 1.0);
+    }
+}
@@ -1,86 +1,706 @@
+use crate::encoding::BRAILLE_OFFSET;
+use crate::encoding::DOTS;
+use crate::fill;
+use crate::stroke;
+use crate::BoundingBox;
+use crate::Color;
+use crate::ColorMode;
+use crate::Composite;
+use crate::Connectivity;
+use crate::Dithering;
+use crate::Encoding;
+use crate::FillRule;
+use crate::Image;
+use crate::ParseError;
+use crate::Raster;
+use crate::Rectangle;
 use crate::Shape;
+use crate::StrokeStyle;
+use crate::Transform;
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
 
-/// ```ignore
-///      0 1 2 3 4           B C D          BRAILLE ⣿
-///     0┌─┬─┬─┬─┐        A┌─┬─┬─┬─┐E       ┌       ┐
-///     1├─┼─┼─┼─┤         │ │ │ │ │         ─▮───▮─
-///     2├─┼─┼─┼─┤        F├─G─H─I─┤J         │   │
-///     3├─┼─┼─┼─┤         │ │ │ │ │         ─▮───▮─
-///     4├─┼─┼─┼─┤        K├─L─M─N─┤O         │   │
-///     5├─┼─┼─┼─┤         │ │ │ │ │         ─▮───▮─
-///     6├─┼─┼─┼─┤        P├─Q─R─S─┤T         │   │
-///     7├─┼─┼─┼─┤         │ │ │ │ │         ─▮───▮─
-///     8└─┴─┴─┴─┘        U└─┴─┴─┴─┘Y       └       ┘
-/// ```                      V W X
-
-pub const DOTS: [[u16; 2]; 4] = [
-    [0x0001, 0x0008],
-    [0x0002, 0x0010],
-    [0x0004, 0x0020],
-    [0x0040, 0x0080],
-];
-pub const BRAILLE_OFFSET: u16 = 0x2800;
+/// ansi escape sequence to reset the color back to the terminal default
+const RESET: &str = "\x1b[0m";
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct Grid {
     width: usize,
-    cells: Vec<u16>,
+    /// the dots of each cell, as bits in the layout of the encoding
+    cells: Vec<u8>,
+    /// the foreground color of each cell, None uses the terminal default
+    colors: Vec<Option<Color>>,
+    encoding: Encoding,
 }
 
 impl Grid {
     pub fn new(width: usize, height: usize) -> Grid {
+        Grid::with_encoding(width, height, Encoding::Braille)
+    }
+
+    /// A grid of `width` x `height` cells, which draws the dots with the
+    /// characters of the encoding
+    pub fn with_encoding(
+        width: usize,
+        height: usize,
+        encoding: Encoding,
+    ) -> Grid {
         Grid {
             width,
-            cells: vec![BRAILLE_OFFSET; width * height],
+            cells: vec![0; width * height],
+            colors: vec![None; width * height],
+            encoding,
+        }
+    }
+
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    /// A braille grid of `width` x `height` cells with the image scaled to
+    /// fill it
+    pub fn from_image(
+        image: &Image,
+        width: usize,
+        height: usize,
+        dithering: Dithering,
+    ) -> Grid {
+        let mut grid = Grid::new(width, height);
+        grid.draw_image(image, dithering);
+        grid
+    }
+
+    /// Draw the image scaled to fill the grid, setting the dots of the dark
+    /// pixels
+    pub fn draw_image(&mut self, image: &Image, dithering: Dithering) {
+        let (dots_wide, dots_high) = self.resolution();
+        let dots = image.resize(dots_wide, dots_high).dither(dithering);
+        for (i, set) in dots.into_iter().enumerate() {
+            if set {
+                self.set_dot(i % dots_wide, i / dots_wide, None);
+            }
+        }
+    }
+
+    /// the number of cells horizontally
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// the number of cells vertically
+    pub fn height(&self) -> usize {
+        self.cells.len().checked_div(self.width).unwrap_or(0)
+    }
+
+    pub fn reset(&mut self) {
+        for c in &mut self.cells {
+            *c = 0;
+        }
+        for c in &mut self.colors {
+            *c = None;
+        }
+    }
+
+    /// the index of the cell and the bit of the dot at dot location
+    /// (dx, dy), None if it is outside of the grid
+    fn locate(&self, dx: usize, dy: usize) -> Option<(usize, u8)> {
+        let (cell_width, cell_height) = self.encoding.cell_size();
+        let (dots_wide, dots_high) = self.resolution();
+        if dx >= dots_wide || dy >= dots_high {
+            return None;
+        }
+        let index = dy / cell_height * self.width + dx / cell_width;
+        let bit = 1 << (dy % cell_height * cell_width + dx % cell_width);
+        Some((index, bit))
+    }
+
+    /// whether the dot at dot location (dx, dy) is lit, false when it is
+    /// outside of the grid
+    pub fn get_dot(&self, dx: usize, dy: usize) -> bool {
+        match self.locate(dx, dy) {
+            Some((index, bit)) => self.cells[index] & bit != 0,
+            None => false,
+        }
+    }
+
+    /// light up the dot at dot location (dx, dy), where the size of each
+    /// cell in dots depends on the encoding.
+    /// The color of the cell is replaced when a color is specified.
+    /// Dots outside of the grid are ignored.
+    pub fn set_dot(&mut self, dx: usize, dy: usize, color: Option<Color>) {
+        if let Some((index, bit)) = self.locate(dx, dy) {
+            self.cells[index] |= bit;
+            if color.is_some() {
+                self.colors[index] = color;
+            }
+        }
+    }
+
+    /// turn off the dot at dot location (dx, dy)
+    pub fn unset_dot(&mut self, dx: usize, dy: usize) {
+        if let Some((index, bit)) = self.locate(dx, dy) {
+            self.cells[index] &= !bit;
+        }
+    }
+
+    /// turn the dot at dot location (dx, dy) off if it is lit, or on if it
+    /// is not
+    pub fn toggle_dot(&mut self, dx: usize, dy: usize) {
+        if let Some((index, bit)) = self.locate(dx, dy) {
+            self.cells[index] ^= bit;
+        }
+    }
+
+    /// iterate over the locations of the lit dots, row by row
+    pub fn lit_dots(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let (dots_wide, dots_high) = self.resolution();
+        (0..dots_high).flat_map(move |dy| {
+            (0..dots_wide)
+                .filter(move |&dx| self.get_dot(dx, dy))
+                .map(move |dx| (dx, dy))
+        })
+    }
+
+    /// the number of dots horizontally and vertically
+    pub fn resolution(&self) -> (usize, usize) {
+        let (cell_width, cell_height) = self.encoding.cell_size();
+        (self.width * cell_width, self.height() * cell_height)
+    }
+
+    /// the color of the cell at (x, y)
+    pub fn color(&self, x: usize, y: usize) -> Option<Color> {
+        self.colors.get(y * self.width + x).copied().flatten()
+    }
+
+    /// the character of the cell at (x, y), a space outside of the grid
+    pub fn char_at(&self, x: usize, y: usize) -> char {
+        if x >= self.width {
+            return ' ';
+        }
+        match self.cells.get(y * self.width + x) {
+            Some(cell) => self.encoding.to_char(*cell),
+            None => ' ',
         }
     }
 
-    pub fn to_string(&self) -> String {
+    /// The grid with the same cells and colors drawn with another encoding.
+    /// Each dot of this grid lights the dots of the new encoding covering
+    /// the same part of its cell.
+    pub fn to_encoding(&self, encoding: Encoding) -> Grid {
+        if encoding == self.encoding {
+            return self.clone();
+        }
+        let (from_width, from_height) = self.encoding.cell_size();
+        let (to_width, to_height) = encoding.cell_size();
+        // the dots of the new encoding covering the dot of this grid
+        let cover = |d: usize, from: usize, to: usize| {
+            d * to / from..((d + 1) * to).div_ceil(from)
+        };
+        let mut grid = Grid::with_encoding(self.width, self.height(), encoding);
+        for (dx, dy) in self.lit_dots() {
+            for y in cover(dy, from_height, to_height) {
+                for x in cover(dx, from_width, to_width) {
+                    grid.set_dot(x, y, None);
+                }
+            }
+        }
+        grid.colors = self.colors.clone();
+        grid
+    }
+
+    /// Combine the dots and colors of `layer` into this grid, which is
+    /// expected to have the same size and encoding
+    pub fn composite(&mut self, layer: &Grid, composite: Composite) {
+        let cells = self.cells.iter_mut().zip(self.colors.iter_mut());
+        let layer_cells = layer.cells.iter().zip(layer.colors.iter());
+        for ((cell, color), (layer_cell, layer_color)) in cells.zip(layer_cells)
+        {
+            match composite {
+                Composite::Or => {
+                    *cell |= layer_cell;
+                    if *layer_cell != 0 && layer_color.is_some() {
+                        *color = *layer_color;
+                    }
+                }
+                Composite::Replace if *layer_cell != 0 => {
+                    *cell = *layer_cell;
+                    *color = *layer_color;
+                }
+                Composite::Replace => (),
+                Composite::Mask => *cell &= layer_cell,
+            }
+        }
+    }
+
+    /// Render the grid with the colors of each cell as ansi escape sequences.
+    /// The color is reset at the end of each line
+    pub fn to_ansi_string(&self, mode: ColorMode) -> String {
         let mut buf = String::new();
-        for (i, cell) in self.cells.iter().enumerate() {
+        let mut current: Option<Color> = None;
+        for (i, (cell, color)) in
+            self.cells.iter().zip(self.colors.iter()).enumerate()
+        {
             if i != 0 && i % self.width == 0 {
+                if current.is_some() {
+                    buf.push_str(RESET);
+                    current = None;
+                }
                 buf.push('\n');
             }
-            let ch = String::from_utf16(&[*cell]).unwrap();
-            if ch == "\u{2800}" {
+            if *cell == 0 {
                 buf.push(' ');
-            } else {
-                buf.push_str(&ch);
+                continue;
+            }
+            if *color != current {
+                match color {
+                    Some(color) => buf.push_str(&color.fg_escape(mode)),
+                    None => buf.push_str(RESET),
+                }
+                current = *color;
             }
+            buf.push(self.encoding.to_char(*cell));
+        }
+        if current.is_some() {
+            buf.push_str(RESET);
         }
         buf
     }
+}
 
-    pub fn reset(&mut self) {
-        for c in &mut self.cells {
-            *c = BRAILLE_OFFSET;
+impl fmt::Display for Grid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, cell) in self.cells.iter().enumerate() {
+            if i != 0 && i % self.width == 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", self.encoding.to_char(*cell))?;
         }
+        Ok(())
     }
 }
 
+impl FromStr for Grid {
+    type Err = ParseError;
+
+    /// Parse the braille characters and spaces of each line into the dots
+    /// of a braille grid, the reverse of `to_string`.
+    /// All lines need to have the same number of characters.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut width = None;
+        let mut cells = vec![];
+        let mut position = 0;
+        for line in s.lines() {
+            let mut line_width = 0;
+            for (i, ch) in line.char_indices() {
+                let code = ch as u32;
+                let braille = match ch {
+                    ' ' => 0,
+                    '\u{2800}'..='\u{28FF}' => code - BRAILLE_OFFSET as u32,
+                    _ => {
+                        return Err(ParseError {
+                            position: position + i,
+                            message: format!(
+                                "{:?} is not a braille character",
+                                ch
+                            ),
+                        })
+                    }
+                };
+                let mut cell = 0;
+                for (row, row_dots) in DOTS.iter().enumerate() {
+                    for (column, dot) in row_dots.iter().enumerate() {
+                        if braille & *dot as u32 != 0 {
+                            cell |= 1 << (row * 2 + column);
+                        }
+                    }
+                }
+                cells.push(cell);
+                line_width += 1;
+            }
+            match width {
+                Some(width) if width != line_width => {
+                    return Err(ParseError {
+                        position,
+                        message: format!(
+                            "expected {} characters in the line, found {}",
+                            width, line_width
+                        ),
+                    })
+                }
+                _ => width = Some(line_width),
+            }
+            // the line ending is either \n or \r\n
+            position += line.len();
+            position += if s[position..].starts_with("\r\n") {
+                2
+            } else {
+                1
+            };
+        }
+        let width = width.unwrap_or(0);
+        let mut grid =
+            Grid::new(width, cells.len().checked_div(width).unwrap_or(0));
+        grid.cells = cells;
+        Ok(grid)
+    }
+}
+
+/// The direction of the y axis
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    /// y increases going down, like in the terminal and svg
+    YDown,
+    /// y increases going up, like in cartesian plots
+    YUp,
+}
+
+/// How the dots of the shapes are combined with the dots already drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DrawMode {
+    /// light up the dots of the shape
+    #[default]
+    Set,
+    /// turn off the dots of the shape, erasing what was drawn there
+    Clear,
+    /// flip the dots of the shape, drawing the shape twice restores the
+    /// canvas
+    Xor,
+}
+
+/// A region the drawing is confined to, as a closed polygon in world
+/// coordinates
+struct Clip {
+    ring: Vec<(f32, f32)>,
+    fill_rule: FillRule,
+}
+
+/// whether the bounds span a finite range of a non zero size
+fn is_valid_span((start, end): (f32, f32)) -> bool {
+    start.is_finite() && end.is_finite() && (end - start).is_normal()
+}
+
 /// Holds the state of the Canvas when painting to it.
 /// width of 1 cell text is 0.5 and height is 1.0
 pub struct Context {
     width: f32,
     height: f32,
+    /// the world coordinates of the left and right edge of the canvas
     x_bounds: (f32, f32),
+    /// the world coordinates of the lowest and highest y of the canvas
     y_bounds: (f32, f32),
+    orientation: Orientation,
+    /// the transform applied to the shapes before they are drawn
+    transform: Transform,
+    /// the transforms saved by `push_transform`
+    transform_stack: Vec<Transform>,
+    /// how the outlines of the shapes are drawn
+    stroke_style: StrokeStyle,
+    /// how the dots of the shapes are combined with the canvas
+    draw_mode: DrawMode,
+    /// the regions pushed by `push_clip_rect` and `push_clip_polygon`,
+    /// the shapes are only drawn where all of them overlap
+    clip_stack: Vec<Clip>,
     grid: Grid,
 }
 
 impl Context {
     pub fn new(width: f32, height: f32) -> Self {
-        let width = width * 2.0;
         Context {
-            width,
+            width: width * 2.0,
             height,
             x_bounds: (0.0, width),
             y_bounds: (0.0, height),
-            grid: Grid::new(width as usize, height as usize),
+            orientation: Orientation::YDown,
+            transform: Transform::IDENTITY,
+            transform_stack: vec![],
+            stroke_style: StrokeStyle::default(),
+            draw_mode: DrawMode::Set,
+            clip_stack: vec![],
+            grid: Grid::new((width * 2.0) as usize, height as usize),
+        }
+    }
+
+    /// Create a context of `width` x `height` character cells, where the
+    /// shapes are drawn in the world coordinates spanning
+    /// `x_bounds` horizontally and `y_bounds` vertically.
+    /// Bounds which are empty or not finite span the cells instead, from 0.0
+    /// to `width` and from 0.0 to `height`.
+    pub fn with_bounds(
+        width: usize,
+        height: usize,
+        x_bounds: (f32, f32),
+        y_bounds: (f32, f32),
+    ) -> Self {
+        Context {
+            width: width as f32,
+            height: height as f32,
+            x_bounds: if is_valid_span(x_bounds) {
+                x_bounds
+            } else {
+                (0.0, width as f32)
+            },
+            y_bounds: if is_valid_span(y_bounds) {
+                y_bounds
+            } else {
+                (0.0, height as f32)
+            },
+            orientation: Orientation::YDown,
+            transform: Transform::IDENTITY,
+            transform_stack: vec![],
+            stroke_style: StrokeStyle::default(),
+            draw_mode: DrawMode::Set,
+            clip_stack: vec![],
+            grid: Grid::new(width, height),
+        }
+    }
+
+    /// the world coordinates of the left and right edge of the canvas
+    pub fn x_bounds(&self) -> (f32, f32) {
+        self.x_bounds
+    }
+
+    /// the world coordinates of the lowest and highest y of the canvas
+    pub fn y_bounds(&self) -> (f32, f32) {
+        self.y_bounds
+    }
+
+    /// Set the world coordinates of the left and right edge, bounds which
+    /// are empty or not finite are ignored
+    pub fn set_x_bounds(&mut self, left: f32, right: f32) {
+        if is_valid_span((left, right)) {
+            self.x_bounds = (left, right);
+        }
+    }
+
+    /// Set the world coordinates of the lowest and highest y, bounds which
+    /// are empty or not finite are ignored
+    pub fn set_y_bounds(&mut self, min: f32, max: f32) {
+        if is_valid_span((min, max)) {
+            self.y_bounds = (min, max);
         }
     }
 
-    pub fn to_string(&self) -> String {
-        self.grid.to_string()
+    pub fn orientation(&self) -> Orientation {
+        self.orientation
+    }
+
+    /// Set the direction of the y axis, with `YUp` the lowest y is at the
+    /// bottom of the canvas
+    pub fn set_orientation(&mut self, orientation: Orientation) {
+        self.orientation = orientation;
+    }
+
+    /// the grid which the shapes are drawn into
+    pub fn grid(&self) -> &Grid {
+        &self.grid
+    }
+
+    /// whether the dot at dot location (dx, dy) is lit
+    pub fn get_dot(&self, dx: usize, dy: usize) -> bool {
+        self.grid.get_dot(dx, dy)
+    }
+
+    /// light up the dot at dot location (dx, dy)
+    pub fn set_dot(&mut self, dx: usize, dy: usize) {
+        self.grid.set_dot(dx, dy, None)
+    }
+
+    /// turn off the dot at dot location (dx, dy)
+    pub fn unset_dot(&mut self, dx: usize, dy: usize) {
+        self.grid.unset_dot(dx, dy)
+    }
+
+    /// flip the dot at dot location (dx, dy)
+    pub fn toggle_dot(&mut self, dx: usize, dy: usize) {
+        self.grid.toggle_dot(dx, dy)
+    }
+
+    /// the dot location of the point (x, y) in world coordinates, with the
+    /// current transform applied, None if it is outside of the canvas
+    fn point_to_dot(&self, x: f32, y: f32) -> Option<(usize, usize)> {
+        let (x, y) = self.transform.apply((x, y));
+        self.world_to_dot(x, y)
+    }
+
+    /// whether the dot at the point (x, y) in world coordinates is lit
+    pub fn get_point(&self, x: f32, y: f32) -> bool {
+        self.point_to_dot(x, y)
+            .map(|(dx, dy)| self.grid.get_dot(dx, dy))
+            .unwrap_or(false)
+    }
+
+    /// light up the dot at the point (x, y) in world coordinates
+    pub fn set_point(&mut self, x: f32, y: f32) {
+        if let Some((dx, dy)) = self.point_to_dot(x, y) {
+            self.grid.set_dot(dx, dy, None);
+        }
+    }
+
+    /// turn off the dot at the point (x, y) in world coordinates
+    pub fn unset_point(&mut self, x: f32, y: f32) {
+        if let Some((dx, dy)) = self.point_to_dot(x, y) {
+            self.grid.unset_dot(dx, dy);
+        }
+    }
+
+    /// flip the dot at the point (x, y) in world coordinates
+    pub fn toggle_point(&mut self, x: f32, y: f32) {
+        if let Some((dx, dy)) = self.point_to_dot(x, y) {
+            self.grid.toggle_dot(dx, dy);
+        }
+    }
+
+    /// Light up the unlit region connected to the dot at (dx, dy), returns
+    /// the number of dots filled or None when the region is larger than
+    /// `max_area`
+    pub fn flood_fill(
+        &mut self,
+        dx: usize,
+        dy: usize,
+        connectivity: Connectivity,
+        max_area: Option<usize>,
+    ) -> Option<usize> {
+        self.grid.flood_fill(dx, dy, connectivity, max_area, None)
+    }
+
+    /// Light up the unlit region connected to the point (x, y) in world
+    /// coordinates, returns the number of dots filled or None when the
+    /// region is larger than `max_area`
+    pub fn flood_fill_point(
+        &mut self,
+        x: f32,
+        y: f32,
+        connectivity: Connectivity,
+        max_area: Option<usize>,
+    ) -> Option<usize> {
+        match self.point_to_dot(x, y) {
+            Some((dx, dy)) => self.flood_fill(dx, dy, connectivity, max_area),
+            None => Some(0),
+        }
+    }
+
+    /// iterate over the locations of the lit dots, row by row
+    pub fn lit_dots(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.grid.lit_dots()
+    }
+
+    /// the characters used to draw the dots of the canvas
+    pub fn encoding(&self) -> Encoding {
+        self.grid.encoding()
+    }
+
+    /// Draw with the characters of the encoding, which changes the number
+    /// of dots in a cell while keeping the size of the canvas in cells and
+    /// its world bounds.
+    /// The canvas is cleared, as the dots drawn so far don't fit the new
+    /// cells.
+    pub fn set_encoding(&mut self, encoding: Encoding) {
+        self.grid = Grid::with_encoding(
+            self.width as usize,
+            self.height as usize,
+            encoding,
+        );
+    }
+
+    /// Move the viewport by (dx, dy) in world units
+    pub fn pan(&mut self, dx: f32, dy: f32) {
+        self.x_bounds = (self.x_bounds.0 + dx, self.x_bounds.1 + dx);
+        self.y_bounds = (self.y_bounds.0 + dy, self.y_bounds.1 + dy);
+    }
+
+    /// Zoom the viewport around its center, a factor greater than 1.0
+    /// zooms in
+    pub fn zoom(&mut self, factor: f32) {
+        let cx = (self.x_bounds.0 + self.x_bounds.1) / 2.0;
+        let cy = (self.y_bounds.0 + self.y_bounds.1) / 2.0;
+        self.zoom_at(cx, cy, factor);
+    }
+
+    /// Zoom the viewport while keeping the world point (x, y) at the same
+    /// location in the canvas
+    pub fn zoom_at(&mut self, x: f32, y: f32, factor: f32) {
+        let scale = |(a, b): (f32, f32), c: f32| {
+            (c + (a - c) / factor, c + (b - c) / factor)
+        };
+        let (x_bounds, y_bounds) =
+            (scale(self.x_bounds, x), scale(self.y_bounds, y));
+        // a zoom which collapses the viewport is ignored
+        if is_valid_span(x_bounds) && is_valid_span(y_bounds) {
+            self.x_bounds = x_bounds;
+            self.y_bounds = y_bounds;
+        }
+    }
+
+    /// the current transform applied to the shapes
+    pub fn transform(&self) -> Transform {
+        self.transform
+    }
+
+    /// Save the current transform and apply `transform` to the shapes
+    /// drawn afterwards, before the previously pushed transforms
+    pub fn push_transform(&mut self, transform: Transform) {
+        self.transform_stack.push(self.transform);
+        self.transform = transform.then(&self.transform);
+    }
+
+    /// Restore the transform before the last `push_transform`
+    pub fn pop_transform(&mut self) {
+        if let Some(transform) = self.transform_stack.pop() {
+            self.transform = transform;
+        }
+    }
+
+    /// Confine the shapes drawn afterwards to the rectangle from (x1, y1) to
+    /// (x2, y2), within the previously pushed clips.
+    /// The current transform is applied to the rectangle.
+    pub fn push_clip_rect(&mut self, x1: f32, y1: f32, x2: f32, y2: f32) {
+        let corners = Rectangle::new(x1, y1, x2, y2).corners();
+        self.push_clip_polygon(&corners, FillRule::NonZero);
+    }
+
+    /// Confine the shapes drawn afterwards to the inside of the polygon,
+    /// within the previously pushed clips.
+    /// The current transform is applied to the polygon.
+    pub fn push_clip_polygon(
+        &mut self,
+        points: &[(f32, f32)],
+        fill_rule: FillRule,
+    ) {
+        let ring = points.iter().map(|p| self.transform.apply(*p)).collect();
+        self.clip_stack.push(Clip { ring, fill_rule });
+    }
+
+    /// Remove the last pushed clip
+    pub fn pop_clip(&mut self) {
+        self.clip_stack.pop();
+    }
+
+    /// the stroke style used to draw the shapes
+    pub fn stroke_style(&self) -> &StrokeStyle {
+        &self.stroke_style
+    }
+
+    /// Set the width, dash pattern, caps and joins of the shapes drawn
+    /// after this
+    pub fn set_stroke_style(&mut self, stroke_style: StrokeStyle) {
+        self.stroke_style = stroke_style;
+    }
+
+    /// how the dots of the shapes are combined with the canvas
+    pub fn draw_mode(&self) -> DrawMode {
+        self.draw_mode
+    }
+
+    /// Set whether the shapes drawn after this light up, erase or flip
+    /// their dots
+    pub fn set_draw_mode(&mut self, draw_mode: DrawMode) {
+        self.draw_mode = draw_mode;
+    }
+
+    /// Render the drawing with the colors as ansi escape sequences
+    pub fn to_ansi_string(&self, mode: ColorMode) -> String {
+        self.grid.to_ansi_string(mode)
     }
 
     /// Draw any object that may implement the Shape trait
@@ -88,30 +708,246 @@ impl Context {
     where
         S: Shape<'b>,
     {
+        self.draw_shape(shape, None)
+    }
+
+    /// Draw the shape with the given foreground color
+    pub fn draw_with_color<'b, S>(&mut self, shape: &'b S, color: Color)
+    where
+        S: Shape<'b>,
+    {
+        self.draw_shape(shape, Some(color))
+    }
+
+    /// the size of a single dot in world units
+    pub fn dot_size(&self) -> f32 {
         let (left, right) = self.x_bounds;
-        let (top, bottom) = self.y_bounds;
-        for (x, y) in shape
-            .points()
-            .map(|(x, y)| (2.0 * x, y))
-            .filter(|&(x, y)| x >= left && x < right && y >= top && y < bottom)
-        {
-            let dy =
-                ((top - y) * (self.height) * 4.0 / (top - bottom)) as usize;
-            let dx =
-                ((x - left) * (self.width) * 2.0 / (right - left)) as usize;
-            let index = dy / 4 * self.width as usize + dx / 2;
-            let dy_index = dy % 4;
-            let dx_index = dx % 2;
-            let braille = DOTS[dy_index][dx_index];
-            self.grid.cells[index] |= braille;
+        let (min, max) = self.y_bounds;
+        let (dots_wide, dots_high) = self.dots();
+        let dot_width = (right - left).abs() / dots_wide;
+        let dot_height = (max - min).abs() / dots_high;
+        dot_width.min(dot_height)
+    }
+
+    /// the number of dots of the canvas horizontally and vertically
+    fn dots(&self) -> (f32, f32) {
+        let (cell_width, cell_height) = self.grid.encoding().cell_size();
+        (
+            self.width * cell_width as f32,
+            self.height * cell_height as f32,
+        )
+    }
+
+    /// The transform from the world coordinates to the dot space of the
+    /// grid, where the dot (dx, dy) spans from dx to dx + 1 horizontally and
+    /// dy to dy + 1 vertically
+    fn dot_transform(&self) -> Transform {
+        let (left, right) = self.x_bounds;
+        let (min, max) = self.y_bounds;
+        let (dots_wide, dots_high) = self.dots();
+        let sx = dots_wide / (right - left);
+        let sy = dots_high / (max - min);
+        match self.orientation {
+            Orientation::YDown => {
+                Transform::new(sx, 0.0, 0.0, sy, -left * sx, -min * sy)
+            }
+            Orientation::YUp => {
+                Transform::new(sx, 0.0, 0.0, -sy, -left * sx, max * sy)
+            }
+        }
+    }
+
+    /// Convert the world coordinate into the dot location in the grid,
+    /// None if it is outside of the canvas
+    pub(crate) fn world_to_dot(
+        &self,
+        x: f32,
+        y: f32,
+    ) -> Option<(usize, usize)> {
+        let (dots_wide, dots_high) = self.dots();
+        let (dx, dy) = self.dot_transform().apply((x, y));
+        if dx >= 0.0 && dx < dots_wide && dy >= 0.0 && dy < dots_high {
+            Some((dx as usize, dy as usize))
+        } else {
+            None
+        }
+    }
+
+    fn draw_shape<'b, S>(&mut self, shape: &'b S, color: Option<Color>)
+    where
+        S: Shape<'b>,
+    {
+        let transform = self.transform;
+        let dot_size = self.dot_size();
+        if self.is_culled(shape, dot_size) {
+            return;
+        }
+        let local_dot_size = transform.local_dot_size(dot_size);
+        let dot_transform = self.dot_transform();
+        let clips: Vec<Clip> = self
+            .clip_stack
+            .iter()
+            .map(|clip| Clip {
+                ring: clip
+                    .ring
+                    .iter()
+                    .map(|p| dot_transform.apply(*p))
+                    .collect(),
+                fill_rule: clip.fill_rule,
+            })
+            .collect();
+        let (dots_wide, dots_high) = self.dots();
+        let mut raster = Raster::new(
+            transform.then(&self.dot_transform()),
+            local_dot_size,
+            dots_wide as usize,
+            dots_high as usize,
+        );
+        shape.fill(&mut raster);
+        let hairline = self.stroke_style.is_hairline();
+        if hairline {
+            shape.rasterize(&mut raster);
+        }
+        let mut dots = raster.into_dots();
+        if !hairline {
+            let mut outlines = shape.outlines(local_dot_size);
+            for outline in outlines.iter_mut() {
+                for vertex in outline.vertices.iter_mut() {
+                    *vertex = transform.apply(*vertex);
+                }
+            }
+            // several points of the stroke may fall into the same dot, or a
+            // dot of the fill, which must only be flipped once
+            let mut seen: HashSet<(usize, usize)> =
+                dots.iter().copied().collect();
+            let stroke = stroke::stroke(
+                &outlines,
+                &self.stroke_style,
+                dot_size,
+                &self.world_bounds(),
+            );
+            for (x, y) in stroke {
+                if let Some(dot) = self.world_to_dot(x, y) {
+                    if seen.insert(dot) {
+                        dots.push(dot);
+                    }
+                }
+            }
+        }
+        for (dx, dy) in dots {
+            if !self.is_clipped(&clips, dx, dy) {
+                self.plot(dx, dy, color);
+            }
+        }
+    }
+
+    /// whether the shape is entirely outside of the bounds of the canvas,
+    /// including the width of the stroke, so it can be skipped.
+    /// Shapes without a finite bounding box are never culled.
+    fn is_culled<'b, S>(&self, shape: &'b S, dot_size: f32) -> bool
+    where
+        S: Shape<'b>,
+    {
+        let corners = match shape.bounding_box() {
+            Some(bounding_box) => bounding_box.corners(),
+            None => return false,
+        };
+        let world: Vec<_> =
+            corners.iter().map(|c| self.transform.apply(*c)).collect();
+        if !world.iter().all(|(x, y)| x.is_finite() && y.is_finite()) {
+            return false;
+        }
+        match BoundingBox::from_points(world) {
+            Some(world) => {
+                // a dot of margin for the rounding of the rasterization
+                let margin = (self.stroke_style.reach() + 1.0) * dot_size;
+                !world.expand(margin).intersects(&self.world_bounds())
+            }
+            None => false,
+        }
+    }
+
+    /// the box of the world coordinates shown on the canvas
+    fn world_bounds(&self) -> BoundingBox {
+        let (left, right) = self.x_bounds;
+        let (min, max) = self.y_bounds;
+        BoundingBox::from_points(vec![(left, min), (right, max)])
+            .unwrap_or(BoundingBox::UNBOUNDED)
+    }
+
+    /// whether the center of the dot is outside of any of the clips, which
+    /// are in dot space
+    fn is_clipped(&self, clips: &[Clip], dx: usize, dy: usize) -> bool {
+        let center = (dx as f32 + 0.5, dy as f32 + 0.5);
+        clips.iter().any(|clip| {
+            !fill::ring_contains(&clip.ring, clip.fill_rule, center)
+        })
+    }
+
+    /// Combine the dot of a shape with the grid according to the draw mode
+    fn plot(&mut self, dx: usize, dy: usize, color: Option<Color>) {
+        match self.draw_mode {
+            DrawMode::Set => self.grid.set_dot(dx, dy, color),
+            DrawMode::Clear => self.grid.unset_dot(dx, dy),
+            DrawMode::Xor if self.grid.get_dot(dx, dy) => {
+                self.grid.unset_dot(dx, dy)
+            }
+            DrawMode::Xor => self.grid.set_dot(dx, dy, color),
         }
     }
 }
 
+impl fmt::Display for Context {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.grid.fmt(f)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::line::Line;
+    use crate::Circle;
+    use crate::Outline;
+    use crate::Rectangle;
+    use std::cell::Cell;
+
+    /// a line which counts how often its outlines are computed, and which
+    /// has no bounding box when it is not `bounded`
+    struct CountedLine {
+        line: Line,
+        bounded: bool,
+        outlines: Cell<usize>,
+    }
+
+    impl CountedLine {
+        fn new(x1: f32, y1: f32, x2: f32, y2: f32, bounded: bool) -> Self {
+            CountedLine {
+                line: Line::new(x1, y1, x2, y2),
+                bounded,
+                outlines: Cell::new(0),
+            }
+        }
+    }
+
+    impl<'a> Shape<'a> for CountedLine {
+        fn points(&'a self) -> Box<dyn Iterator<Item = (f32, f32)> + 'a> {
+            self.line.points()
+        }
+
+        fn outlines(&'a self, dot_size: f32) -> Vec<Outline> {
+            self.outlines.set(self.outlines.get() + 1);
+            self.line.outlines(dot_size)
+        }
+
+        fn bounding_box(&'a self) -> Option<BoundingBox> {
+            if self.bounded {
+                self.line.bounding_box()
+            } else {
+                None
+            }
+        }
+    }
 
     #[test]
     fn draw_cell_horizontal_line() {
@@ -165,7 +1001,7 @@ mod tests {
             y2: 0.0,
         });
         let result = context.to_string();
-        assert_eq!(result, "⡰");
+        assert_eq!(result, "⡜");
     }
 
     #[test]
@@ -179,7 +1015,7 @@ mod tests {
             y2: 0.375,
         });
         let result = context.to_string();
-        assert_eq!(result, "⡠");
+        assert_eq!(result, "⠔");
     }
 
     #[test]
@@ -202,7 +1038,7 @@ mod tests {
     fn draw_vertical_lines() {
         let width = 1.0;
         let height = 10.0;
-        let mut context = Context::new(width as f32, height as f32);
+        let mut context = Context::new(width, height);
 
         context.draw(&Line {
             x1: 0.5,
@@ -221,7 +1057,7 @@ mod tests {
         let width = 10.0;
         let height = 10.0;
 
-        let mut context = Context::new(width as f32, height as f32);
+        let mut context = Context::new(width, height);
         context.draw(&Line {
             x1: 0.0,
             y1: 0.0,
@@ -250,7 +1086,7 @@ mod tests {
     fn draw_slash_lines2() {
         let width = 10.0;
         let height = 10.0;
-        let mut context = Context::new(width as f32, height as f32);
+        let mut context = Context::new(width, height);
 
         context.draw(&Line {
             x1: width,
@@ -261,7 +1097,7 @@ mod tests {
         let result = context.to_string();
         println!("{}", result);
         let expected = [
-            "⠐⢄                  ",
+            "⠑⢄                  ",
             "  ⠑⢄                ",
             "    ⠑⢄              ",
             "      ⠑⢄            ",
@@ -279,7 +1115,7 @@ mod tests {
     fn draw_slant_lines1() {
         let width = 10.0;
         let height = 10.0;
-        let mut context = Context::new(width as f32, height as f32);
+        let mut context = Context::new(width, height);
 
         context.draw(&Line {
             x1: 0.0,
@@ -291,16 +1127,16 @@ mod tests {
         println!("{}", result);
 
         let expected = [
-            "                  ⢀⠔",
-            "                ⢀⠔⠁ ",
-            "              ⢀⠔⠁   ",
-            "            ⢀⠔⠁     ",
-            "          ⢀⠔⠁       ",
-            "        ⢀⠔⠁         ",
-            "      ⢀⠔⠁           ",
-            "    ⢀⠔⠁             ",
-            "  ⢀⠔⠁               ",
-            "⢀⠔⠁                 ",
+            "                  ⡠⠊",
+            "                ⡠⠊  ",
+            "              ⡠⠊    ",
+            "            ⡠⠊      ",
+            "          ⡠⠊        ",
+            "        ⡠⠊          ",
+            "      ⡠⠊            ",
+            "    ⡠⠊              ",
+            "  ⡠⠊                ",
+            "⡠⠊                  ",
         ];
 
         assert_eq!(result, expected.join("\n"));
@@ -310,7 +1146,7 @@ mod tests {
     fn draw_slant_lines2() {
         let width = 10.0;
         let height = 10.0;
-        let mut context = Context::new(width as f32, height as f32);
+        let mut context = Context::new(width, height);
 
         context.draw(&Line {
             x1: width - 0.5,
@@ -322,18 +1158,217 @@ mod tests {
         println!("{}", result);
 
         let expected = [
-            "                 ⢀⠔⠁",
-            "               ⢀⠔⠁  ",
-            "             ⢀⠔⠁    ",
+            "                 ⡠⠊ ",
+            "               ⡠⠊   ",
+            "             ⢀⠎     ",
             "           ⢀⠔⠁      ",
             "         ⢀⠔⠁        ",
-            "        ⡠⠊          ",
-            "      ⡠⠊            ",
-            "    ⡠⠊              ",
+            "       ⢀⠔⠁          ",
+            "     ⢀⠔⠁            ",
+            "    ⡰⠁              ",
             "  ⡠⠊                ",
             "⡠⠊                  ",
         ];
 
         assert_eq!(result, expected.join("\n"));
     }
+
+    #[test]
+    fn draw_colored_lines() {
+        let mut context = Context::new(2.0, 1.0);
+        context.draw_with_color(&Line::new(0.0, 0.5, 0.75, 0.5), Color::RED);
+        context.draw(&Line::new(1.0, 0.5, 2.0, 0.5));
+
+        assert_eq!(context.to_string(), "⠤⠤⠤⠤");
+        assert_eq!(
+            context.to_ansi_string(ColorMode::Ansi16),
+            "\x1b[31m⠤⠤\x1b[0m⠤⠤"
+        );
+        assert_eq!(
+            context.to_ansi_string(ColorMode::TrueColor),
+            "\x1b[38;2;128;0;0m⠤⠤\x1b[0m⠤⠤"
+        );
+    }
+
+    #[test]
+    fn draw_in_world_bounds() {
+        let mut context = Context::with_bounds(8, 2, (-1.0, 1.0), (-1.0, 1.0));
+        context.set_orientation(Orientation::YUp);
+        context.draw(&Line::new(-1.0, -1.0, 1.0, 1.0));
+        let result = context.to_string();
+        println!("{}", result);
+        let expected = ["    ⣀⠤⠒⠉", "⣀⠤⠒⠉    "];
+        assert_eq!(result, expected.join("\n"));
+    }
+
+    #[test]
+    fn pan_and_zoom() {
+        let mut context = Context::with_bounds(4, 2, (0.0, 4.0), (0.0, 2.0));
+        context.zoom(2.0);
+        assert_eq!(context.x_bounds(), (1.0, 3.0));
+        assert_eq!(context.y_bounds(), (0.5, 1.5));
+        context.pan(-1.0, 0.5);
+        assert_eq!(context.x_bounds(), (0.0, 2.0));
+        assert_eq!(context.y_bounds(), (1.0, 2.0));
+        context.zoom_at(0.0, 1.0, 0.5);
+        assert_eq!(context.x_bounds(), (0.0, 4.0));
+        assert_eq!(context.y_bounds(), (1.0, 3.0));
+    }
+
+    #[test]
+    fn draw_with_encodings() {
+        let mut context = Context::new(4.0, 2.0);
+        let expected = [
+            (Encoding::HalfBlock, ["█▀██▀▀▀█", "█▄▄▄██▄█"]),
+            (Encoding::Quadrant, ["▛▀██▀▀▀▜", "▙▄▄▄██▄▟"]),
+            (Encoding::Sextant, ["🬕🬊🬥🬰🬂🬂🬂🬨", "🬲🬭🬭🬭🬰🬶🬱🬷"]),
+            (Encoding::Ascii, [":'::''':", ":...::.:"]),
+        ];
+        for (encoding, expected) in expected.iter() {
+            context.set_encoding(*encoding);
+            context.draw(&Rectangle::new(0.0, 0.0, 3.75, 1.75));
+            context.draw(&Line::new(0.0, 0.0, 4.0, 2.0));
+            let result = context.to_string();
+            println!("{}", result);
+            assert_eq!(result, expected.join("\n"));
+        }
+    }
+
+    #[test]
+    fn get_set_and_toggle_dots() {
+        let mut context = Context::new(1.0, 1.0);
+        context.set_dot(0, 0);
+        context.set_point(0.75, 0.75);
+        context.toggle_dot(1, 1);
+        context.toggle_dot(1, 1);
+        context.toggle_dot(2, 0);
+        context.set_dot(100, 100);
+        assert!(context.get_dot(3, 3));
+        assert!(context.get_point(0.8, 0.8));
+        assert!(!context.get_dot(1, 1));
+        assert_eq!(context.to_string(), "⠁⢁");
+        assert_eq!(
+            context.lit_dots().collect::<Vec<_>>(),
+            vec![(0, 0), (2, 0), (3, 3)]
+        );
+        context.unset_point(0.0, 0.0);
+        context.unset_dot(2, 0);
+        assert_eq!(context.lit_dots().collect::<Vec<_>>(), vec![(3, 3)]);
+    }
+
+    #[test]
+    fn draw_modes() {
+        let mut context = Context::new(2.0, 1.0);
+        context.draw(&Rectangle::new(0.0, 0.0, 1.75, 0.75));
+        let frame = context.to_string();
+
+        context.set_draw_mode(DrawMode::Xor);
+        context.draw(&Line::new(0.0, 0.0, 2.0, 1.0));
+        let result = context.to_string();
+        println!("{}", result);
+        assert_eq!(result, "⣆⣛⣭⠹");
+        context.draw(&Line::new(0.0, 0.0, 2.0, 1.0));
+        assert_eq!(context.to_string(), frame);
+
+        context.set_stroke_style(StrokeStyle::new(2.0));
+        context.draw(&Line::new(0.0, 0.5, 2.0, 0.5));
+        context.draw(&Line::new(0.0, 0.5, 2.0, 0.5));
+        assert_eq!(context.to_string(), frame);
+
+        context.set_draw_mode(DrawMode::Clear);
+        context.draw(&Line::new(0.0, 0.0, 2.0, 0.0));
+        let result = context.to_string();
+        println!("{}", result);
+        assert_eq!(result, "⣆⣀⣀⣰");
+    }
+
+    #[test]
+    fn clip_to_rect_and_polygon() {
+        let mut context = Context::new(4.0, 2.0);
+        context.push_clip_rect(1.0, 0.5, 3.0, 2.0);
+        context.draw(&Rectangle::new(0.0, 0.0, 3.75, 1.75));
+        context.draw(&Line::new(0.0, 0.0, 4.0, 2.0));
+        context.push_clip_polygon(
+            &[(2.0, 0.0), (4.0, 2.0), (0.0, 2.0)],
+            FillRule::NonZero,
+        );
+        context.draw(&Line::new(0.0, 1.5, 4.0, 1.5));
+        context.pop_clip();
+        context.pop_clip();
+        context.draw(&Line::new(0.0, 0.0, 1.0, 0.0));
+        let result = context.to_string();
+        println!("{}", result);
+        let expected = ["⠉⠉⠥⣀    ", "  ⣤⣤⣭⣶  "];
+        assert_eq!(result, expected.join("\n"));
+    }
+
+    #[test]
+    fn parse_braille() {
+        let mut context = Context::new(2.0, 2.0);
+        context.draw(&Circle::new(1.0, 1.0, 0.75));
+        let text = context.to_string();
+        let grid: Grid = text.parse().unwrap();
+        assert_eq!(grid.to_string(), text);
+        assert_eq!(&grid, context.grid());
+        assert_eq!(grid.lit_dots().count(), context.lit_dots().count());
+        assert_eq!((grid.width(), grid.height()), (4, 2));
+
+        let grid: Grid = "\u{2800}⣿\r\n⠁ \n".parse().unwrap();
+        assert_eq!(grid.to_string(), " ⣿\n⠁ ");
+        assert!(grid.get_dot(0, 4));
+        assert_eq!("".parse::<Grid>().unwrap().to_string(), "");
+    }
+
+    #[test]
+    fn parse_braille_errors() {
+        let err = "⣿⣿\n⣿x".parse::<Grid>().unwrap_err();
+        assert_eq!(err.position, 10);
+        let err = "⣿⣿\n⣿".parse::<Grid>().unwrap_err();
+        assert_eq!(err.position, 7);
+        assert_eq!(err.message, "expected 2 characters in the line, found 1");
+    }
+
+    #[test]
+    fn cull_shapes_outside_of_bounds() {
+        let mut context = Context::new(2.0, 1.0);
+        let outside = CountedLine::new(-1.0, 1.5, 3.0, 1.5, true);
+        context.draw(&outside);
+        assert_eq!(outside.outlines.get(), 0);
+        assert_eq!(context.lit_dots().count(), 0);
+        // the wide stroke reaches into the canvas
+        context.set_stroke_style(StrokeStyle::new(4.0));
+        let wide = CountedLine::new(-1.0, 1.2, 3.0, 1.2, true);
+        context.draw(&wide);
+        assert!(wide.outlines.get() > 0);
+        let result = context.to_string();
+        println!("{}", result);
+        assert_eq!(result, "⣀⣀⣀⣀");
+    }
+
+    #[test]
+    fn draw_shapes_without_a_finite_bounding_box() {
+        let mut context = Context::new(2.0, 1.0);
+        let unbounded = CountedLine::new(0.0, 0.5, 2.0, 0.5, false);
+        context.draw(&unbounded);
+        assert_eq!(unbounded.outlines.get(), 1);
+        assert_eq!(context.to_string(), "⠤⠤⠤⠤");
+        // the center of an arc from a point to itself is not a number
+        let arc = crate::Arc::new(1.0, 0.5, 1.0, 0.5, 0.5, false);
+        assert_eq!(arc.bounding_box(), None);
+        assert!(!context.is_culled(&arc, 0.25));
+    }
+
+    #[test]
+    fn empty_bounds_are_ignored() {
+        let mut context = Context::new(2.0, 1.0);
+        context.set_x_bounds(1.0, 1.0);
+        context.set_y_bounds(0.0, f32::NAN);
+        context.zoom(f32::INFINITY);
+        context.zoom(0.0);
+        assert_eq!(context.x_bounds(), (0.0, 2.0));
+        assert_eq!(context.y_bounds(), (0.0, 1.0));
+        context.draw(&Circle::new(1.0, 1.0, 0.5));
+        let context = Context::with_bounds(4, 1, (3.0, 3.0), (0.0, 1.0));
+        assert_eq!(context.x_bounds(), (0.0, 4.0));
+    }
 }
@@ -0,0 +1,440 @@
+//! Loading of netpbm images (PBM, PGM and PPM) as grayscale, to be drawn
+//! into a `Grid` with dithering.
+use crate::ParseError;
+use std::fmt;
+use std::io;
+
+/// A grayscale image, where a luminance of 0 is black and 255 is white
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    /// the luminance of the pixels, row by row
+    pub pixels: Vec<u8>,
+}
+
+/// An error while loading an image file
+#[derive(Debug)]
+pub enum ImageError {
+    Io(io::Error),
+    Parse(ParseError),
+}
+
+/// How the gray levels of an image are converted into dots, which are
+/// either set or not
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Dithering {
+    /// the pixels darker than the luminance are set
+    Threshold(u8),
+    /// ordered dithering with a 4x4 Bayer matrix
+    Bayer,
+    /// Floyd–Steinberg error diffusion
+    FloydSteinberg,
+}
+
+/// the 4x4 Bayer threshold matrix
+const BAYER: [[u8; 4]; 4] =
+    [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ImageError::Io(e) => write!(f, "{}", e),
+            ImageError::Parse(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for ImageError {}
+
+impl From<io::Error> for ImageError {
+    fn from(e: io::Error) -> Self {
+        ImageError::Io(e)
+    }
+}
+
+impl From<ParseError> for ImageError {
+    fn from(e: ParseError) -> Self {
+        ImageError::Parse(e)
+    }
+}
+
+impl Image {
+    /// an image of the pixels, which the callers in this crate size to be
+    /// `width` x `height`
+    pub(crate) fn new(width: usize, height: usize, pixels: Vec<u8>) -> Self {
+        debug_assert_eq!(pixels.len(), width * height);
+        Image {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// load a local PBM, PGM or PPM image file
+    pub fn load<P: AsRef<std::path::Path>>(
+        path: P,
+    ) -> Result<Self, ImageError> {
+        let content = std::fs::read(path)?;
+        Ok(Image::parse(&content)?)
+    }
+
+    /// Parse a netpbm image in any of the plain (P1, P2, P3) or raw (P4, P5,
+    /// P6) formats. Colors are converted to their luminance.
+    pub fn parse(data: &[u8]) -> Result<Self, ParseError> {
+        let mut reader = Reader { data, pos: 0 };
+        let magic = reader.token()?;
+        let format = match magic {
+            b"P1" | b"P2" | b"P3" | b"P4" | b"P5" | b"P6" => magic[1] - b'0',
+            _ => return Err(reader.error(0, "not a PBM, PGM or PPM image")),
+        };
+        let width = reader.number()? as usize;
+        let height = reader.number()? as usize;
+        let maxval = match format {
+            1 | 4 => 1,
+            _ => reader.number()?,
+        };
+        if maxval == 0 || maxval > 65535 {
+            return Err(reader.error(reader.pos, "invalid maximum value"));
+        }
+        let count = width
+            .checked_mul(height)
+            .ok_or_else(|| reader.error(reader.pos, "image is too large"))?;
+        // a pixel takes at least a bit of the data
+        let mut pixels = Vec::with_capacity(count.min(data.len() * 8));
+        match format {
+            // plain bitmap, where 1 is black
+            1 => {
+                for _ in 0..count {
+                    let bit = reader.bit()?;
+                    pixels.push(if bit { 0 } else { 255 });
+                }
+            }
+            2 | 3 => {
+                let samples = if format == 3 { 3 } else { 1 };
+                for _ in 0..count {
+                    let mut values = [0; 3];
+                    for value in values.iter_mut().take(samples) {
+                        *value = reader.sample(maxval)?;
+                    }
+                    pixels.push(gray(&values[..samples]));
+                }
+            }
+            // raw bitmap, with each row padded to whole bytes
+            4 => {
+                reader.pos += 1;
+                let row_bytes = width.div_ceil(8);
+                let bytes = reader.take(row_bytes * height)?;
+                for y in 0..height {
+                    for x in 0..width {
+                        let byte = bytes[y * row_bytes + x / 8];
+                        let bit = byte & (0x80 >> (x % 8)) != 0;
+                        pixels.push(if bit { 0 } else { 255 });
+                    }
+                }
+            }
+            _ => {
+                reader.pos += 1;
+                let samples = if format == 6 { 3 } else { 1 };
+                let sample_bytes = if maxval > 255 { 2 } else { 1 };
+                let len =
+                    count.checked_mul(samples * sample_bytes).ok_or_else(
+                        || reader.error(reader.pos, "image is too large"),
+                    )?;
+                let bytes = reader.take(len)?;
+                for pixel in bytes.chunks(samples * sample_bytes) {
+                    let values: Vec<u8> = pixel
+                        .chunks(sample_bytes)
+                        .map(|sample| {
+                            let value = sample
+                                .iter()
+                                .fold(0u32, |acc, b| acc << 8 | *b as u32);
+                            scale(value.min(maxval), maxval)
+                        })
+                        .collect();
+                    pixels.push(gray(&values));
+                }
+            }
+        }
+        Ok(Image::new(width, height, pixels))
+    }
+
+    /// the image with black and white swapped
+    pub fn invert(&self) -> Image {
+        Image::new(
+            self.width,
+            self.height,
+            self.pixels.iter().map(|p| 255 - p).collect(),
+        )
+    }
+
+    /// The image scaled to `width` x `height` pixels.
+    /// Each pixel is the average of the pixels of the area it covers in
+    /// this image, or the nearest pixel when scaling up.
+    pub fn resize(&self, width: usize, height: usize) -> Image {
+        let mut pixels = Vec::with_capacity(width * height);
+        if self.width == 0 || self.height == 0 {
+            return Image::new(width, height, vec![255; width * height]);
+        }
+        let range = |i: usize, to: usize, from: usize| {
+            let start = i * from / to;
+            let end = ((i + 1) * from / to).max(start + 1);
+            start..end
+        };
+        for y in 0..height {
+            let rows = range(y, height, self.height);
+            for x in 0..width {
+                let columns = range(x, width, self.width);
+                let mut sum = 0;
+                let mut count = 0;
+                for sy in rows.clone() {
+                    for sx in columns.clone() {
+                        sum += self.pixels[sy * self.width + sx] as usize;
+                        count += 1;
+                    }
+                }
+                pixels.push((sum / count) as u8);
+            }
+        }
+        Image::new(width, height, pixels)
+    }
+
+    /// Which pixels are set as dots, row by row, where the dark pixels are
+    /// the ones set
+    pub fn dither(&self, dithering: Dithering) -> Vec<bool> {
+        match dithering {
+            Dithering::Threshold(level) => {
+                self.pixels.iter().map(|p| *p < level).collect()
+            }
+            Dithering::Bayer => self
+                .pixels
+                .iter()
+                .enumerate()
+                .map(|(i, p)| {
+                    let (x, y) = (i % self.width, i / self.width);
+                    let threshold = (BAYER[y % 4][x % 4] as u32 * 2 + 1) * 8;
+                    (*p as u32) < threshold
+                })
+                .collect(),
+            Dithering::FloydSteinberg => {
+                let mut values: Vec<f32> =
+                    self.pixels.iter().map(|p| *p as f32).collect();
+                let mut dots = Vec::with_capacity(values.len());
+                for y in 0..self.height {
+                    for x in 0..self.width {
+                        let i = y * self.width + x;
+                        let set = values[i] < 128.0;
+                        let error = values[i] - if set { 0.0 } else { 255.0 };
+                        dots.push(set);
+                        let mut spread = |dx: isize, dy: usize, weight: f32| {
+                            let nx = x as isize + dx;
+                            if nx >= 0
+                                && (nx as usize) < self.width
+                                && y + dy < self.height
+                            {
+                                values[(y + dy) * self.width + nx as usize] +=
+                                    error * weight / 16.0;
+                            }
+                        };
+                        spread(1, 0, 7.0);
+                        spread(-1, 1, 3.0);
+                        spread(0, 1, 5.0);
+                        spread(1, 1, 1.0);
+                    }
+                }
+                dots
+            }
+        }
+    }
+}
+
+/// scale the sample to the range of 0..=255
+fn scale(value: u32, maxval: u32) -> u8 {
+    ((value * 255 + maxval / 2) / maxval) as u8
+}
+
+/// the luminance of a gray or rgb pixel
+fn gray(values: &[u8]) -> u8 {
+    match values {
+        [r, g, b] => {
+            ((*r as u32 * 299 + *g as u32 * 587 + *b as u32 * 114 + 500) / 1000)
+                as u8
+        }
+        [value] => *value,
+        _ => 0,
+    }
+}
+
+/// Reads the tokens of the header and plain formats
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn error(&self, position: usize, message: &str) -> ParseError {
+        ParseError {
+            position,
+            message: message.to_string(),
+        }
+    }
+
+    /// skip the whitespace and comments
+    fn skip(&mut self) {
+        while let Some(&b) = self.data.get(self.pos) {
+            if b == b'#' {
+                while self.pos < self.data.len() && self.data[self.pos] != b'\n'
+                {
+                    self.pos += 1;
+                }
+            } else if b.is_ascii_whitespace() {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn token(&mut self) -> Result<&'a [u8], ParseError> {
+        self.skip();
+        let start = self.pos;
+        while self.pos < self.data.len()
+            && !self.data[self.pos].is_ascii_whitespace()
+            && self.data[self.pos] != b'#'
+        {
+            self.pos += 1;
+        }
+        if start == self.pos {
+            Err(self.error(start, "unexpected end of image"))
+        } else {
+            Ok(&self.data[start..self.pos])
+        }
+    }
+
+    fn number(&mut self) -> Result<u32, ParseError> {
+        let token = self.token()?;
+        let start = self.pos - token.len();
+        std::str::from_utf8(token)
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .ok_or_else(|| self.error(start, "expecting a number"))
+    }
+
+    /// a sample of the plain gray and color formats
+    fn sample(&mut self, maxval: u32) -> Result<u8, ParseError> {
+        let start = self.pos;
+        let value = self.number()?;
+        if value > maxval {
+            return Err(self.error(start, "sample is larger than the maximum"));
+        }
+        Ok(scale(value, maxval))
+    }
+
+    /// a pixel of the plain bitmap format, which doesn't need whitespace in
+    /// between
+    fn bit(&mut self) -> Result<bool, ParseError> {
+        self.skip();
+        match self.data.get(self.pos) {
+            Some(b'0') => {
+                self.pos += 1;
+                Ok(false)
+            }
+            Some(b'1') => {
+                self.pos += 1;
+                Ok(true)
+            }
+            Some(_) => Err(self.error(self.pos, "expecting 0 or 1")),
+            None => Err(self.error(self.pos, "unexpected end of image")),
+        }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], ParseError> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .ok_or_else(|| self.error(self.pos, "image is too large"))?;
+        if end > self.data.len() {
+            return Err(self.error(self.data.len(), "unexpected end of image"));
+        }
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn parse_formats() {
+        let pbm = Image::parse(b"P1\n# comment\n3 2\n010\n1 0 1").unwrap();
+        assert_eq!(pbm.pixels, vec![255, 0, 255, 0, 255, 0]);
+
+        let pgm = Image::parse(b"P2 2 1 15 0 15").unwrap();
+        assert_eq!(pgm.pixels, vec![0, 255]);
+
+        let ppm =
+            Image::parse(b"P6 2 1 255\n\xff\x00\x00\x00\x00\xff").unwrap();
+        assert_eq!(ppm.pixels, vec![76, 29]);
+
+        let raw_pbm = Image::parse(b"P4 10 1\n\xc0\x40").unwrap();
+        assert_eq!(raw_pbm.pixels[..3], [0, 0, 255]);
+        assert_eq!(raw_pbm.pixels[9], 0);
+
+        let wide = Image::parse(b"P5 1 1 65535\n\x80\x00").unwrap();
+        assert_eq!(wide.pixels, vec![128]);
+    }
+
+    #[test]
+    fn parse_errors() {
+        let err = Image::parse(b"P2 2 1 15 0 x").err().unwrap();
+        assert_eq!(err.position, 12);
+        let err = Image::parse(b"P5 2 2 255\n\x00").err().unwrap();
+        assert_eq!(err.message, "unexpected end of image");
+        assert!(Image::parse(b"GIF89a").is_err());
+        let err = Image::parse(b"P6 4294967295 4294967295 255\n").err();
+        assert_eq!(err.unwrap().message, "image is too large");
+    }
+
+    #[test]
+    fn resize_averages_the_pixels() {
+        let image = Image::new(4, 2, vec![0, 0, 255, 255, 0, 100, 255, 255]);
+        assert_eq!(image.resize(2, 1).pixels, vec![25, 255]);
+        assert_eq!(image.resize(8, 2).pixels[..4], [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn dither_gray() {
+        let gray = Image::new(8, 8, vec![128; 64]);
+        for &dithering in &[Dithering::Bayer, Dithering::FloydSteinberg] {
+            let dots = gray.dither(dithering);
+            assert_eq!(dots.iter().filter(|d| **d).count(), 32);
+        }
+        let dots = gray.dither(Dithering::Threshold(100));
+        assert!(dots.iter().all(|d| !d));
+    }
+
+    #[test]
+    fn draw_image() {
+        // a dark circle on a white background
+        let size = 16;
+        let pixels = (0..size * size)
+            .map(|i| {
+                let (x, y) = ((i % size) as f32 - 7.5, (i / size) as f32 - 7.5);
+                if x * x + y * y < 40.0 {
+                    0
+                } else {
+                    255
+                }
+            })
+            .collect();
+        let image = Image::new(size, size, pixels);
+        let grid = Grid::from_image(&image, 4, 2, Dithering::Threshold(128));
+        let result = grid.to_string();
+        println!("{}", result);
+        let expected = ["⢠⣶⣶⡄", "⠘⠿⠿⠃"];
+        assert_eq!(result, expected.join("\n"));
+    }
+}
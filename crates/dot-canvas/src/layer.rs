@@ -0,0 +1,205 @@
+//! Named layers of drawings, which are flattened into a single `Grid`
+use crate::Context;
+use crate::Grid;
+
+/// How the dots of a layer are combined with the layers below it
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Composite {
+    /// the dots of the layer are added to the dots below
+    #[default]
+    Or,
+    /// the cells where the layer has dots replace the cells below,
+    /// including their color
+    Replace,
+    /// only the dots below which are also lit in the layer are kept
+    Mask,
+}
+
+/// A drawing which is shown above or below the other layers
+pub struct Layer {
+    pub name: String,
+    /// hidden layers are left out when flattening
+    pub visible: bool,
+    /// layers with a higher z index are drawn above the others, layers
+    /// with the same z index are drawn in the order they were added
+    pub z_index: i32,
+    pub composite: Composite,
+    context: Context,
+}
+
+impl Layer {
+    /// the context which the shapes of this layer are drawn into
+    pub fn context(&self) -> &Context {
+        &self.context
+    }
+
+    pub fn context_mut(&mut self) -> &mut Context {
+        &mut self.context
+    }
+}
+
+/// A stack of named layers of the same size and world bounds
+pub struct Layers {
+    width: usize,
+    height: usize,
+    x_bounds: (f32, f32),
+    y_bounds: (f32, f32),
+    layers: Vec<Layer>,
+}
+
+impl Layers {
+    /// Create layers of `width` x `height` character cells, where the
+    /// shapes are drawn in the world coordinates spanning
+    /// `x_bounds` horizontally and `y_bounds` vertically
+    pub fn new(
+        width: usize,
+        height: usize,
+        x_bounds: (f32, f32),
+        y_bounds: (f32, f32),
+    ) -> Self {
+        Layers {
+            width,
+            height,
+            x_bounds,
+            y_bounds,
+            layers: vec![],
+        }
+    }
+
+    /// Add a visible layer above the existing layers, returning its context
+    /// to draw into. An existing layer of the same name is replaced.
+    pub fn add_layer(&mut self, name: &str) -> &mut Context {
+        self.remove_layer(name);
+        let z_index = self.layers.iter().map(|l| l.z_index + 1).max();
+        self.layers.push(Layer {
+            name: name.to_string(),
+            visible: true,
+            z_index: z_index.unwrap_or(0),
+            composite: Composite::Or,
+            context: Context::with_bounds(
+                self.width,
+                self.height,
+                self.x_bounds,
+                self.y_bounds,
+            ),
+        });
+        &mut self.layers.last_mut().unwrap().context
+    }
+
+    /// Remove the layer with the name, returning it
+    pub fn remove_layer(&mut self, name: &str) -> Option<Layer> {
+        let index = self.layers.iter().position(|l| l.name == name)?;
+        Some(self.layers.remove(index))
+    }
+
+    pub fn layer(&self, name: &str) -> Option<&Layer> {
+        self.layers.iter().find(|l| l.name == name)
+    }
+
+    pub fn layer_mut(&mut self, name: &str) -> Option<&mut Layer> {
+        self.layers.iter_mut().find(|l| l.name == name)
+    }
+
+    /// the layers in the order they were added
+    pub fn layers(&self) -> impl Iterator<Item = &Layer> {
+        self.layers.iter()
+    }
+
+    /// Show or hide the layer with the name
+    pub fn set_visible(&mut self, name: &str, visible: bool) {
+        if let Some(layer) = self.layer_mut(name) {
+            layer.visible = visible;
+        }
+    }
+
+    /// Combine the visible layers from the lowest z index to the highest
+    /// into a single grid, with the encoding of the lowest layer.
+    /// Layers with another encoding are redrawn in that encoding first.
+    pub fn flatten(&self) -> Grid {
+        let mut layers: Vec<&Layer> =
+            self.layers.iter().filter(|l| l.visible).collect();
+        // the sort is stable, which keeps the order of the equal z indices
+        layers.sort_by_key(|l| l.z_index);
+        let encoding = match layers.first() {
+            Some(layer) => layer.context.encoding(),
+            None => Default::default(),
+        };
+        let mut grid = Grid::with_encoding(self.width, self.height, encoding);
+        for layer in layers {
+            let layer_grid = layer.context.grid();
+            if layer_grid.encoding() == encoding {
+                grid.composite(layer_grid, layer.composite);
+            } else {
+                let reencoded = layer_grid.to_encoding(encoding);
+                grid.composite(&reencoded, layer.composite);
+            }
+        }
+        grid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    fn layers() -> Layers {
+        let mut layers = Layers::new(4, 1, (0.0, 2.0), (0.0, 1.0));
+        layers
+            .add_layer("grid")
+            .draw(&Rectangle::new(0.0, 0.0, 1.75, 0.75));
+        layers
+            .add_layer("data")
+            .draw_with_color(&Line::new(0.0, 1.0, 2.0, 0.0), Color::RED);
+        layers
+    }
+
+    #[test]
+    fn flatten_visible_layers() {
+        let mut layers = layers();
+        let result = layers.flatten().to_string();
+        println!("{}", result);
+        let expected = ["⣏⣭⣛⣹"];
+        assert_eq!(result, expected.join("\n"));
+
+        layers.set_visible("grid", false);
+        let result = layers.flatten().to_string();
+        println!("{}", result);
+        let expected = ["⣀⠤⠒⠉"];
+        assert_eq!(result, expected.join("\n"));
+    }
+
+    #[test]
+    fn composite_modes() {
+        let mut layers = layers();
+        layers.layer_mut("grid").unwrap().z_index = 2;
+        layers.layer_mut("grid").unwrap().composite = Composite::Mask;
+        let result = layers.flatten().to_string();
+        println!("{}", result);
+        let expected = ["⣀  ⠉"];
+        assert_eq!(result, expected.join("\n"));
+
+        layers.layer_mut("grid").unwrap().composite = Composite::Replace;
+        let grid = layers.flatten();
+        let result = grid.to_string();
+        println!("{}", result);
+        let expected = ["⣏⣉⣉⣹"];
+        assert_eq!(result, expected.join("\n"));
+        assert_eq!(grid.color(0, 0), None);
+    }
+
+    #[test]
+    fn flatten_layers_with_other_encodings() {
+        let mut layers = Layers::new(2, 1, (0.0, 1.0), (0.0, 1.0));
+        layers
+            .add_layer("braille")
+            .draw(&Line::new(0.0, 0.9, 1.0, 0.9));
+        let blocks = layers.add_layer("blocks");
+        blocks.set_encoding(Encoding::HalfBlock);
+        blocks.draw(&Line::new(0.0, 0.1, 1.0, 0.1));
+        let result = layers.flatten().to_string();
+        println!("{}", result);
+        let expected = ["⣛⣛"];
+        assert_eq!(result, expected.join("\n"));
+    }
+}
@@ -2,14 +2,82 @@
 //! Ito-canvas is a drawing canvas using braille to display the drawing in the terminal
 //!
 pub use arc::Arc;
+pub use bar::BarChart;
+pub use bar::BarDirection;
+pub use bar::Histogram;
+pub use bezier::CubicBezier;
+pub use bezier::QuadBezier;
 pub use circle::Circle;
+pub use color::Color;
+pub use color::ColorMode;
+pub use ellipse::ArcCenter;
+pub use ellipse::Ellipse;
+pub use ellipse::EllipticalArc;
+pub use encoding::Encoding;
+pub use export::SvgDot;
+pub use fill::Connectivity;
+pub use fill::FillRule;
+pub use fill::FilledCircle;
+pub use fill::FilledPolygon;
+pub use fill::FilledRectangle;
 pub use grid::Context;
+pub use grid::DrawMode;
 pub use grid::Grid;
+pub use grid::Orientation;
+pub use image::Dithering;
+pub use image::Image;
+pub use image::ImageError;
+pub use layer::Composite;
+pub use layer::Layer;
+pub use layer::Layers;
 pub use line::Line;
+pub use plot::ticks;
+pub use plot::Plot;
+pub use plot::SeriesStyle;
+pub use polygon::Polygon;
+pub use polygon::Polyline;
+pub use polygon::Rectangle;
+pub use raster::Raster;
+pub use render::Renderer;
+pub use shape::BoundingBox;
+pub use shape::Dot;
+pub use shape::Outline;
 pub use shape::Shape;
+pub use sparkline::Sparkline;
+pub use stroke::LineCap;
+pub use stroke::LineJoin;
+pub use stroke::StrokeStyle;
+pub use svg::Element;
+pub use svg::ParseError;
+pub use svg::Path;
+pub use svg::Segment;
+pub use svg::Svg;
+pub use svg::SvgError;
+pub use transform::Transform;
+pub use transform::Transformed;
+pub use turtle::LSystem;
+pub use turtle::Turtle;
 
 mod arc;
+mod bar;
+mod bezier;
 mod circle;
+mod color;
+mod ellipse;
+mod encoding;
+mod export;
+mod fill;
 mod grid;
+mod image;
+mod layer;
 mod line;
+mod plot;
+mod polygon;
+mod raster;
+mod render;
 mod shape;
+mod sparkline;
+mod stroke;
+mod svg;
+mod transform;
+mod turtle;
@@ -1,6 +1,10 @@
+use crate::shape::valid_step;
+use crate::shape::STEP;
+use crate::Outline;
 use crate::Shape;
 
-/// Shape to draw a line from (x1, y1) to (x2, y2) with the given color
+/// Shape to draw a line from (x1, y1) to (x2, y2).
+/// Use `Context::draw_with_color` to draw it with a color
 pub struct Line {
     pub x1: f32,
     pub y1: f32,
@@ -23,6 +27,7 @@ pub struct LineIterator {
     dir_y: f32,
     current: f32,
     end: f32,
+    step: f32,
 }
 
 impl Iterator for LineIterator {
@@ -34,8 +39,7 @@ impl Iterator for LineIterator {
                 self.x + (self.current * self.dx) / self.end * self.dir_x,
                 self.y + (self.current * self.dy) / self.end * self.dir_y,
             );
-            //self.current += 1.0;
-            self.current += 0.25;
+            self.current += self.step;
             Some(pos)
         } else {
             None
@@ -43,16 +47,19 @@ impl Iterator for LineIterator {
     }
 }
 
-impl<'a> IntoIterator for &'a Line {
-    type Item = (f32, f32);
-    type IntoIter = LineIterator;
-
-    fn into_iter(self) -> Self::IntoIter {
+impl Line {
+    /// iterate over the points of this line, which are `step` apart.
+    /// A step that is not a positive finite number is replaced by the
+    /// default step, and a line with an infinite length has no points.
+    pub fn iter_with_step(&self, step: f32) -> LineIterator {
         let dx = self.x1.max(self.x2) - self.x1.min(self.x2);
         let dy = self.y1.max(self.y2) - self.y1.min(self.y2);
         let dir_x = if self.x1 <= self.x2 { 1.0 } else { -1.0 };
         let dir_y = if self.y1 <= self.y2 { 1.0 } else { -1.0 };
-        let end = dx.max(dy);
+        let end = match dx.max(dy) {
+            end if end.is_finite() => end,
+            _ => 0.0,
+        };
         LineIterator {
             x: self.x1,
             y: self.y1,
@@ -62,12 +69,33 @@ impl<'a> IntoIterator for &'a Line {
             dir_y,
             current: 0.0,
             end,
+            step: valid_step(step),
         }
     }
 }
 
+impl IntoIterator for &Line {
+    type Item = (f32, f32);
+    type IntoIter = LineIterator;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_with_step(STEP)
+    }
+}
+
 impl<'a> Shape<'a> for Line {
     fn points(&'a self) -> Box<dyn Iterator<Item = (f32, f32)> + 'a> {
         Box::new(self.into_iter())
     }
+
+    fn points_at_resolution(
+        &'a self,
+        dot_size: f32,
+    ) -> Box<dyn Iterator<Item = (f32, f32)> + 'a> {
+        Box::new(self.iter_with_step(dot_size))
+    }
+
+    fn outlines(&'a self, _dot_size: f32) -> Vec<Outline> {
+        vec![Outline::open(vec![(self.x1, self.y1), (self.x2, self.y2)])]
+    }
 }
@@ -0,0 +1,380 @@
+//! Plots of functions and point series, drawn into a `Context` with axes
+use crate::Color;
+use crate::Context;
+use crate::Dot;
+use crate::Line;
+use crate::Orientation;
+use crate::Polyline;
+use crate::Shape;
+
+/// the number of samples of a function used to find the range of its values
+const FIT_SAMPLES: usize = 256;
+
+/// How the points of a series are drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeriesStyle {
+    /// the points are connected by lines
+    Line,
+    /// a dot is drawn at each point
+    Scatter,
+}
+
+enum Data {
+    /// the function is sampled at every column of dots in the x range
+    Function {
+        f: Box<dyn Fn(f32) -> f32>,
+        x_range: (f32, f32),
+    },
+    Points(Vec<(f32, f32)>),
+}
+
+struct Series {
+    data: Data,
+    style: SeriesStyle,
+    color: Option<Color>,
+}
+
+/// A chart of several series, where the world bounds of the context are
+/// fitted to the data when it is drawn
+pub struct Plot {
+    series: Vec<Series>,
+    /// the fixed x range, instead of the range of the data
+    x_range: Option<(f32, f32)>,
+    /// the fixed y range, instead of the range of the data
+    y_range: Option<(f32, f32)>,
+    /// draw the x and y axis, which cross at 0 when it is in range
+    pub axes: bool,
+    /// the approximate number of tick marks on each axis
+    pub ticks: usize,
+}
+
+impl Default for Plot {
+    fn default() -> Self {
+        Plot::new()
+    }
+}
+
+impl Plot {
+    pub fn new() -> Self {
+        Plot {
+            series: vec![],
+            x_range: None,
+            y_range: None,
+            axes: true,
+            ticks: 5,
+        }
+    }
+
+    /// Plot the function over the x range as a line, values which are not
+    /// finite leave a gap
+    pub fn add_function<F>(
+        &mut self,
+        f: F,
+        x_range: (f32, f32),
+        color: Option<Color>,
+    ) where
+        F: Fn(f32) -> f32 + 'static,
+    {
+        self.series.push(Series {
+            data: Data::Function {
+                f: Box::new(f),
+                x_range,
+            },
+            style: SeriesStyle::Line,
+            color,
+        });
+    }
+
+    /// Plot the points as a line, in the order given
+    pub fn add_line(&mut self, points: &[(f32, f32)], color: Option<Color>) {
+        self.add_points(points, SeriesStyle::Line, color);
+    }
+
+    /// Plot a dot at each of the points
+    pub fn add_scatter(&mut self, points: &[(f32, f32)], color: Option<Color>) {
+        self.add_points(points, SeriesStyle::Scatter, color);
+    }
+
+    pub fn add_points(
+        &mut self,
+        points: &[(f32, f32)],
+        style: SeriesStyle,
+        color: Option<Color>,
+    ) {
+        self.series.push(Series {
+            data: Data::Points(points.to_vec()),
+            style,
+            color,
+        });
+    }
+
+    /// Use a fixed x range instead of fitting it to the data
+    pub fn set_x_range(&mut self, min: f32, max: f32) {
+        self.x_range = Some((min, max));
+    }
+
+    /// Use a fixed y range instead of fitting it to the data
+    pub fn set_y_range(&mut self, min: f32, max: f32) {
+        self.y_range = Some((min, max));
+    }
+
+    /// The x and y range of the plot, which is the fixed range or else the
+    /// range spanned by the finite values of the series, inside the x range
+    /// when it is fixed.
+    /// Empty ranges are widened to span at least 1.0, and a fixed range that
+    /// is not finite is replaced by 0.0 to 1.0.
+    pub fn bounds(&self) -> ((f32, f32), (f32, f32)) {
+        let everything = (f32::NEG_INFINITY, f32::INFINITY);
+        let x_window = self.x_range.map_or(everything, |r| widen(ordered(r)));
+        let mut x_fit = (f32::INFINITY, f32::NEG_INFINITY);
+        let mut y_fit = (f32::INFINITY, f32::NEG_INFINITY);
+        let mut include = |(x, y): (f32, f32)| {
+            if x.is_finite()
+                && y.is_finite()
+                && (x_window.0..=x_window.1).contains(&x)
+            {
+                x_fit = (x_fit.0.min(x), x_fit.1.max(x));
+                y_fit = (y_fit.0.min(y), y_fit.1.max(y));
+            }
+        };
+        // there is nothing to fit when both ranges are fixed
+        let series = match (self.x_range, self.y_range) {
+            (Some(_), Some(_)) => &[][..],
+            _ => &self.series[..],
+        };
+        for series in series.iter() {
+            match &series.data {
+                Data::Function { f, x_range } => {
+                    // only the part of the function which is visible
+                    let (min, max) = ordered(*x_range);
+                    let visible = (min.max(x_window.0), max.min(x_window.1));
+                    if visible.0 <= visible.1 {
+                        for x in sample(visible, FIT_SAMPLES) {
+                            include((x, f(x)));
+                        }
+                    }
+                }
+                Data::Points(points) => points.iter().for_each(|p| include(*p)),
+            }
+        }
+        (
+            self.x_range.map_or_else(|| widen(x_fit), |_| x_window),
+            widen(self.y_range.map(ordered).unwrap_or(y_fit)),
+        )
+    }
+
+    /// Fit the bounds of the context to the plot and draw the axes and the
+    /// series, with the y axis going up
+    pub fn draw(&self, context: &mut Context) {
+        let ((left, right), (bottom, top)) = self.bounds();
+        let (dots_wide, dots_high) = context.grid().resolution();
+        // pad the bounds by half a dot, so the extremes of the data are at
+        // the center of the dots at the edges of the canvas
+        let dot_width = (right - left) / (dots_wide.max(2) - 1) as f32;
+        let dot_height = (top - bottom) / (dots_high.max(2) - 1) as f32;
+        context.set_x_bounds(left - dot_width / 2.0, right + dot_width / 2.0);
+        context.set_y_bounds(bottom - dot_height / 2.0, top + dot_height / 2.0);
+        context.set_orientation(Orientation::YUp);
+
+        if self.axes {
+            let x0 = 0.0f32.max(left).min(right);
+            let y0 = 0.0f32.max(bottom).min(top);
+            context.draw(&Line::new(left, y0, right, y0));
+            context.draw(&Line::new(x0, bottom, x0, top));
+            for x in ticks(left, right, self.ticks) {
+                context.draw(&Line::new(
+                    x,
+                    y0 - dot_height,
+                    x,
+                    y0 + dot_height,
+                ));
+            }
+            for y in ticks(bottom, top, self.ticks) {
+                context.draw(&Line::new(x0 - dot_width, y, x0 + dot_width, y));
+            }
+        }
+
+        for series in self.series.iter() {
+            let points = match &series.data {
+                Data::Function { f, x_range } => {
+                    // one sample at the center of each visible column of dots
+                    let (min, max) = ordered(*x_range);
+                    let columns = (dots_wide.max(2) - 1) as i64;
+                    let first =
+                        (((min - left) / dot_width).ceil() as i64).max(0);
+                    let last = (((max - left) / dot_width).floor() as i64)
+                        .min(columns);
+                    (first..=last)
+                        .map(|column| {
+                            let x = left + column as f32 * dot_width;
+                            (x, f(x))
+                        })
+                        .collect()
+                }
+                Data::Points(points) => points.clone(),
+            };
+            match series.style {
+                SeriesStyle::Line => {
+                    // the non finite points split the line
+                    for run in points
+                        .split(|(x, y)| !x.is_finite() || !y.is_finite())
+                        .filter(|run| !run.is_empty())
+                    {
+                        draw(context, &Polyline::new(run), series.color);
+                    }
+                }
+                SeriesStyle::Scatter => {
+                    for (x, y) in points {
+                        draw(context, &Dot { x, y }, series.color);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn draw<'b, S>(context: &mut Context, shape: &'b S, color: Option<Color>)
+where
+    S: Shape<'b>,
+{
+    match color {
+        Some(color) => context.draw_with_color(shape, color),
+        None => context.draw(shape),
+    }
+}
+
+/// the n + 1 evenly spaced values from the start to the end of the range
+fn sample((start, end): (f32, f32), n: usize) -> impl Iterator<Item = f32> {
+    (0..=n).map(move |i| start + (end - start) * i as f32 / n as f32)
+}
+
+/// the range with its smallest value first
+fn ordered((a, b): (f32, f32)) -> (f32, f32) {
+    (a.min(b), a.max(b))
+}
+
+/// the range, or 0.0 to 1.0 when there is no data or it is not finite,
+/// widened to span at least 1.0
+fn widen((min, max): (f32, f32)) -> (f32, f32) {
+    if !min.is_finite() || !max.is_finite() || min > max {
+        (0.0, 1.0)
+    } else if max - min < f32::EPSILON * max.abs().max(1.0) {
+        (min - 0.5, max + 0.5)
+    } else {
+        (min, max)
+    }
+}
+
+/// The positions of about `count` tick marks in between min and max,
+/// at multiples of 1, 2 or 5 times a power of 10
+pub fn ticks(min: f32, max: f32, count: usize) -> Vec<f32> {
+    let (min, max) = (min.min(max), min.max(max));
+    if count == 0 || !(max - min).is_finite() || max == min {
+        return vec![];
+    }
+    let rough = (max - min) / count as f32;
+    let magnitude = 10f32.powf(rough.log10().floor());
+    let step = [1.0, 2.0, 5.0, 10.0]
+        .iter()
+        .map(|m| m * magnitude)
+        .find(|step| *step >= rough)
+        .unwrap_or(10.0 * magnitude);
+    let first = (min / step).ceil() as i64;
+    let last = (max / step).floor() as i64;
+    (first..=last).map(|i| i as f32 * step).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn nice_ticks() {
+        assert_eq!(ticks(0.0, 10.0, 5), vec![0.0, 2.0, 4.0, 6.0, 8.0, 10.0]);
+        assert_eq!(ticks(-1.0, 1.0, 4), vec![-1.0, -0.5, 0.0, 0.5, 1.0]);
+        assert_eq!(ticks(3.0, 3.0, 4), vec![]);
+    }
+
+    #[test]
+    fn auto_fit_bounds() {
+        let mut plot = Plot::new();
+        plot.add_scatter(&[(1.0, 2.0), (3.0, f32::NAN), (-1.0, 4.0)], None);
+        plot.add_function(|x| x * x, (0.0, 3.0), None);
+        assert_eq!(plot.bounds(), ((-1.0, 3.0), (0.0, 9.0)));
+        plot.set_y_range(-1.0, 1.0);
+        assert_eq!(plot.bounds(), ((-1.0, 3.0), (-1.0, 1.0)));
+        assert_eq!(Plot::new().bounds(), ((0.0, 1.0), (0.0, 1.0)));
+    }
+
+    #[test]
+    fn fixed_ranges_are_widened() {
+        let mut plot = Plot::new();
+        plot.set_x_range(1.0, 1.0);
+        plot.set_y_range(2.0, -2.0);
+        assert_eq!(plot.bounds(), ((0.5, 1.5), (-2.0, 2.0)));
+        plot.set_x_range(f32::NAN, f32::INFINITY);
+        assert_eq!(plot.bounds(), ((0.0, 1.0), (-2.0, 2.0)));
+        plot.set_x_range(1.0, 1.0);
+        plot.add_line(&[(1.0, 0.0)], None);
+        let mut context = Context::with_bounds(2, 1, (0.0, 1.0), (0.0, 1.0));
+        plot.draw(&mut context);
+    }
+
+    #[test]
+    fn fit_only_inside_the_fixed_range() {
+        let mut plot = Plot::new();
+        plot.add_scatter(&[(0.0, 1.0), (1.0, 2.0), (10.0, 100.0)], None);
+        plot.add_function(|x| -x * x, (0.0, 10.0), None);
+        plot.set_x_range(0.0, 1.0);
+        assert_eq!(plot.bounds(), ((0.0, 1.0), (-1.0, 2.0)));
+    }
+
+    #[test]
+    fn functions_are_sampled_in_the_visible_range() {
+        let samples = std::rc::Rc::new(std::cell::Cell::new(0));
+        let counter = samples.clone();
+        let mut plot = Plot::new();
+        plot.add_function(
+            move |x| {
+                counter.set(counter.get() + 1);
+                x
+            },
+            (-1e9, 1e9),
+            None,
+        );
+        plot.set_x_range(0.0, 1.0);
+        plot.set_y_range(0.0, 1.0);
+        samples.set(0);
+        let mut context = Context::with_bounds(4, 1, (0.0, 1.0), (0.0, 1.0));
+        plot.draw(&mut context);
+        assert_eq!(samples.get(), 8);
+    }
+
+    #[test]
+    fn plot_function_with_axes() {
+        let mut plot = Plot::new();
+        plot.add_function(|x| x.sin(), (-3.0, 3.0), None);
+        let mut context = Context::with_bounds(12, 3, (0.0, 1.0), (0.0, 1.0));
+        plot.draw(&mut context);
+        let result = context.to_string();
+        println!("{}", result);
+        let expected = ["     ⢀⣇⠔⠉⠉⠢⡀", "⢤⠤⡦⠤⠤⡤⡯⠤⠤⢴⠤⠵", "⠈⠢⣀⣀⠔⠉⡏     "];
+        assert_eq!(result, expected.join("\n"));
+    }
+
+    #[test]
+    fn plot_lines_and_scatter() {
+        let mut plot = Plot::new();
+        plot.axes = false;
+        plot.add_line(&[(0.0, 0.0), (1.0, 2.0), (2.0, 1.0), (3.0, 3.0)], None);
+        plot.add_scatter(&[(0.0, 3.0), (3.0, 0.0)], Some(Color::RED));
+        let mut context = Context::with_bounds(4, 2, (0.0, 1.0), (0.0, 1.0));
+        plot.draw(&mut context);
+        let result = context.to_string();
+        println!("{}", result);
+        let expected = ["⠁⣄ ⡜", "⡜ ⠙⢀"];
+        assert_eq!(result, expected.join("\n"));
+        assert_eq!(context.grid().color(3, 1), Some(Color::RED));
+    }
+}
@@ -0,0 +1,206 @@
+use crate::shape::STEP;
+use crate::FillRule;
+use crate::FilledPolygon;
+use crate::FilledRectangle;
+use crate::Line;
+use crate::Outline;
+use crate::Shape;
+use std::iter::FromIterator;
+
+/// Connected line segments passing through each of the points
+pub struct Polyline {
+    pub points: Vec<(f32, f32)>,
+}
+
+/// A closed polyline, where the last point connects back to the first point
+pub struct Polygon {
+    pub points: Vec<(f32, f32)>,
+}
+
+/// An axis aligned rectangle with corners (x1, y1) and (x2, y2)
+pub struct Rectangle {
+    pub x1: f32,
+    pub y1: f32,
+    pub x2: f32,
+    pub y2: f32,
+}
+
+impl Polyline {
+    pub fn new(points: &[(f32, f32)]) -> Self {
+        Polyline {
+            points: points.to_vec(),
+        }
+    }
+}
+
+impl Polygon {
+    pub fn new(points: &[(f32, f32)]) -> Self {
+        Polygon {
+            points: points.to_vec(),
+        }
+    }
+
+    /// a filled version of this polygon
+    pub fn filled(&self, fill_rule: FillRule) -> FilledPolygon {
+        FilledPolygon::new(&self.points, fill_rule)
+    }
+}
+
+impl Rectangle {
+    pub fn new(x1: f32, y1: f32, x2: f32, y2: f32) -> Self {
+        Rectangle { x1, y1, x2, y2 }
+    }
+
+    /// the corners of this rectangle in drawing order
+    pub fn corners(&self) -> [(f32, f32); 4] {
+        [
+            (self.x1, self.y1),
+            (self.x2, self.y1),
+            (self.x2, self.y2),
+            (self.x1, self.y2),
+        ]
+    }
+
+    /// a filled version of this rectangle
+    pub fn filled(&self) -> FilledRectangle {
+        FilledRectangle::new(self.x1, self.y1, self.x2, self.y2)
+    }
+}
+
+impl FromIterator<(f32, f32)> for Polyline {
+    fn from_iter<I: IntoIterator<Item = (f32, f32)>>(iter: I) -> Self {
+        Polyline {
+            points: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl FromIterator<(f32, f32)> for Polygon {
+    fn from_iter<I: IntoIterator<Item = (f32, f32)>>(iter: I) -> Self {
+        Polygon {
+            points: iter.into_iter().collect(),
+        }
+    }
+}
+
+/// Points of the segments connecting each consecutive vertices.
+/// The line iterator excludes the end point of each segment, so shared
+/// vertices are only emitted once, by the segment that starts at it.
+fn segment_points<'a>(
+    vertices: impl Iterator<Item = (&'a (f32, f32), &'a (f32, f32))> + 'a,
+    step: f32,
+) -> impl Iterator<Item = (f32, f32)> + 'a {
+    vertices.flat_map(move |(&(x1, y1), &(x2, y2))| {
+        Line::new(x1, y1, x2, y2).iter_with_step(step)
+    })
+}
+
+impl<'a> Shape<'a> for Polyline {
+    fn points(&'a self) -> Box<dyn Iterator<Item = (f32, f32)> + 'a> {
+        self.points_at_resolution(STEP)
+    }
+
+    fn points_at_resolution(
+        &'a self,
+        dot_size: f32,
+    ) -> Box<dyn Iterator<Item = (f32, f32)> + 'a> {
+        let segments = segment_points(
+            self.points.iter().zip(self.points.iter().skip(1)),
+            dot_size,
+        );
+        Box::new(segments.chain(self.points.last().copied()))
+    }
+
+    fn outlines(&'a self, _dot_size: f32) -> Vec<Outline> {
+        vec![Outline::open(self.points.clone())]
+    }
+}
+
+impl<'a> Shape<'a> for Polygon {
+    fn points(&'a self) -> Box<dyn Iterator<Item = (f32, f32)> + 'a> {
+        self.points_at_resolution(STEP)
+    }
+
+    fn points_at_resolution(
+        &'a self,
+        dot_size: f32,
+    ) -> Box<dyn Iterator<Item = (f32, f32)> + 'a> {
+        match self.points.len() {
+            0 => Box::new(std::iter::empty()),
+            1 => Box::new(self.points.iter().copied()),
+            _ => Box::new(segment_points(
+                self.points.iter().zip(self.points.iter().cycle().skip(1)),
+                dot_size,
+            )),
+        }
+    }
+
+    fn outlines(&'a self, _dot_size: f32) -> Vec<Outline> {
+        vec![Outline::closed(self.points.clone())]
+    }
+}
+
+impl<'a> Shape<'a> for Rectangle {
+    fn points(&'a self) -> Box<dyn Iterator<Item = (f32, f32)> + 'a> {
+        self.points_at_resolution(STEP)
+    }
+
+    fn points_at_resolution(
+        &'a self,
+        dot_size: f32,
+    ) -> Box<dyn Iterator<Item = (f32, f32)> + 'a> {
+        let polygon = Polygon::new(&self.corners());
+        let points: Vec<(f32, f32)> =
+            polygon.points_at_resolution(dot_size).collect();
+        Box::new(points.into_iter())
+    }
+
+    fn outlines(&'a self, _dot_size: f32) -> Vec<Outline> {
+        vec![Outline::closed(self.corners().to_vec())]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn polyline_has_no_duplicate_vertices() {
+        let polyline = Polyline::new(&[(0.0, 0.0), (1.0, 0.0), (1.0, 1.0)]);
+        let points: Vec<(f32, f32)> = polyline.points().collect();
+        assert_eq!(points.len(), 9);
+        assert_eq!(points.iter().filter(|p| **p == (1.0, 0.0)).count(), 1);
+        assert_eq!(points.last(), Some(&(1.0, 1.0)));
+    }
+
+    #[test]
+    fn polygon_closes_back_to_first_point() {
+        let polygon: Polygon = vec![(0.0, 0.0), (1.0, 0.0), (1.0, 1.0)]
+            .into_iter()
+            .collect();
+        let points: Vec<(f32, f32)> = polygon.points().collect();
+        assert_eq!(points.iter().filter(|p| **p == (0.0, 0.0)).count(), 1);
+        assert_eq!(points.len(), 12);
+    }
+
+    #[test]
+    fn draw_rectangle() {
+        let mut context = Context::new(5.0, 4.0);
+        context.draw(&Rectangle::new(0.0, 0.0, 4.5, 3.5));
+        let result = context.to_string();
+        println!("{}", result);
+        let expected = ["⡏⠉⠉⠉⠉⠉⠉⠉⠉⡇", "⡇        ⡇", "⡇        ⡇", "⠧⠤⠤⠤⠤⠤⠤⠤⠤⠇"];
+        assert_eq!(result, expected.join("\n"));
+    }
+
+    #[test]
+    fn draw_polyline() {
+        let mut context = Context::new(4.0, 2.0);
+        context.draw(&Polyline::new(&[(0.0, 0.0), (1.0, 1.0), (2.0, 0.0)]));
+        let result = context.to_string();
+        println!("{}", result);
+        let expected = ["⠑⢄⡠⠊⠁   ", "  ⠁     "];
+        assert_eq!(result, expected.join("\n"));
+    }
+}
@@ -0,0 +1,414 @@
+//! Rasterization of the shapes in the integer dot space of the canvas.
+//! Lines use Bresenham's algorithm and axis aligned circles and ellipses
+//! use the midpoint ellipse algorithm, so every dot of a shape is set
+//! exactly once and the result doesn't depend on the drawing direction.
+use crate::BoundingBox;
+use crate::Transform;
+use std::collections::HashSet;
+use std::f32::consts::PI;
+
+/// the number of fractional bits of the fixed point dot coordinates
+const SUBDOT_BITS: u32 = 8;
+
+/// ellipses with a larger radius in half dots are drawn as polylines,
+/// which are clipped to the canvas
+const MAX_RADIUS: i64 = 1 << 16;
+
+/// Collects the dots of a shape drawn into a canvas
+pub struct Raster {
+    /// maps the coordinates of the shape to the dot space of the canvas
+    transform: Transform,
+    /// the size of a dot in the coordinates of the shape
+    dot_size: f32,
+    /// the number of dots horizontally and vertically
+    dots_wide: i64,
+    dots_high: i64,
+    dots: Vec<(usize, usize)>,
+    seen: HashSet<(i64, i64)>,
+}
+
+impl Raster {
+    pub(crate) fn new(
+        transform: Transform,
+        dot_size: f32,
+        dots_wide: usize,
+        dots_high: usize,
+    ) -> Self {
+        Raster {
+            transform,
+            dot_size,
+            dots_wide: dots_wide as i64,
+            dots_high: dots_high as i64,
+            dots: vec![],
+            seen: HashSet::new(),
+        }
+    }
+
+    /// the size of a dot in the coordinates of the shape, used to flatten
+    /// curves finely enough
+    pub fn dot_size(&self) -> f32 {
+        self.dot_size
+    }
+
+    /// The box in the coordinates of the shape around the dots of the canvas,
+    /// with a dot of margin, so shapes can skip the parts that aren't
+    /// visible.
+    /// The box is unbounded when the transform collapses the plane.
+    pub fn visible_bounds(&self) -> BoundingBox {
+        let inverse = match self.transform.inverse() {
+            Some(inverse) => inverse,
+            None => return BoundingBox::UNBOUNDED,
+        };
+        let (w, h) = (self.dots_wide as f32, self.dots_high as f32);
+        let corners: Vec<(f32, f32)> = [(0.0, 0.0), (w, 0.0), (w, h), (0.0, h)]
+            .iter()
+            .map(|corner| inverse.apply(*corner))
+            .collect();
+        if !corners.iter().all(|(x, y)| x.is_finite() && y.is_finite()) {
+            return BoundingBox::UNBOUNDED;
+        }
+        BoundingBox::from_points(corners)
+            .map_or(BoundingBox::UNBOUNDED, |b| b.expand(self.dot_size))
+    }
+
+    /// the dots set so far, in the order they were set
+    pub(crate) fn into_dots(self) -> Vec<(usize, usize)> {
+        self.dots
+    }
+
+    /// Rasterize with the transform applied to the coordinates of the shape
+    pub fn with_transform<F>(&mut self, transform: &Transform, f: F)
+    where
+        F: FnOnce(&mut Raster),
+    {
+        let (saved_transform, saved_dot_size) = (self.transform, self.dot_size);
+        self.dot_size = transform.local_dot_size(self.dot_size);
+        self.transform = transform.then(&self.transform);
+        f(self);
+        self.transform = saved_transform;
+        self.dot_size = saved_dot_size;
+    }
+
+    fn set(&mut self, x: i64, y: i64) {
+        if x >= 0
+            && x < self.dots_wide
+            && y >= 0
+            && y < self.dots_high
+            && self.seen.insert((x, y))
+        {
+            self.dots.push((x as usize, y as usize));
+        }
+    }
+
+    /// the location in the dot space of the point in the coordinates of the
+    /// shape
+    fn to_dot(&self, p: (f32, f32)) -> (f32, f32) {
+        self.transform.apply(p)
+    }
+
+    pub fn point(&mut self, p: (f32, f32)) {
+        let (x, y) = self.to_dot(p);
+        if x.is_finite() && y.is_finite() {
+            self.set(x.floor() as i64, y.floor() as i64);
+        }
+    }
+
+    pub fn line(&mut self, p0: (f32, f32), p1: (f32, f32)) {
+        let bounds = (self.dots_wide as f32, self.dots_high as f32);
+        if let Some((p0, p1)) = clip(self.to_dot(p0), self.to_dot(p1), bounds) {
+            for (x, y) in line_dots(to_fixed(p0), to_fixed(p1)) {
+                self.set(x, y);
+            }
+        }
+    }
+
+    /// the connected lines through the vertices, a single vertex is drawn
+    /// as a point
+    pub fn polyline(&mut self, vertices: &[(f32, f32)], closed: bool) {
+        match vertices {
+            [] => (),
+            [p] => self.point(*p),
+            _ => {
+                for pair in vertices.windows(2) {
+                    self.line(pair[0], pair[1]);
+                }
+                if closed {
+                    self.line(vertices[vertices.len() - 1], vertices[0]);
+                }
+            }
+        }
+    }
+
+    /// an axis aligned ellipse centered at (cx, cy)
+    pub fn ellipse(&mut self, cx: f32, cy: f32, rx: f32, ry: f32) {
+        self.elliptical_arc(cx, cy, rx, ry, 0.0, 2.0 * PI);
+    }
+
+    /// The part of an axis aligned ellipse from the start angle sweeping
+    /// through the sweep angle, both in radians.
+    /// When the shape is rotated or skewed, the arc is drawn as a flattened
+    /// polyline instead.
+    pub fn elliptical_arc(
+        &mut self,
+        cx: f32,
+        cy: f32,
+        rx: f32,
+        ry: f32,
+        start_angle: f32,
+        sweep_angle: f32,
+    ) {
+        let (rx, ry) = (rx.abs(), ry.abs());
+        let t = self.transform;
+        let (dcx, dcy) = self.to_dot((cx, cy));
+        // the center and radius in half dots
+        let center = ((dcx * 2.0).round() as i64, (dcy * 2.0).round() as i64);
+        let radius = (
+            (rx * t.a.abs() * 2.0).round() as i64,
+            (ry * t.d.abs() * 2.0).round() as i64,
+        );
+        if t.b != 0.0
+            || t.c != 0.0
+            || !(dcx.is_finite() && dcy.is_finite())
+            || radius.0.max(radius.1) > MAX_RADIUS
+        {
+            let vertices = crate::ellipse::sample_ellipse(
+                cx,
+                cy,
+                rx,
+                ry,
+                0.0,
+                start_angle,
+                sweep_angle,
+                self.dot_size,
+            );
+            self.polyline(&vertices, false);
+            return;
+        }
+        let full = sweep_angle.abs() >= 2.0 * PI;
+        // the sweep starts at 0, so the angles are compared in one range
+        let (start, sweep) = if sweep_angle < 0.0 {
+            (start_angle + sweep_angle, -sweep_angle)
+        } else {
+            (start_angle, sweep_angle)
+        };
+        // half of a dot of tolerance, so the end points are included
+        let tolerance = 1.0 / radius.0.max(radius.1).max(1) as f32;
+        for (x, y) in midpoint_ellipse(center, radius) {
+            if !full {
+                // the direction of the dot in the coordinates of the shape
+                let dx = (x as f32 + 0.5 - dcx) / t.a;
+                let dy = (y as f32 + 0.5 - dcy) / t.d;
+                let angle = (dy.atan2(dx) - start).rem_euclid(2.0 * PI);
+                if angle > sweep + tolerance && angle < 2.0 * PI - tolerance {
+                    continue;
+                }
+            }
+            self.set(x, y);
+        }
+    }
+}
+
+/// Clip the line to the area around the canvas, so far away end points
+/// don't make the line take long to rasterize.
+/// Lines that are inside of the area are returned unchanged.
+fn clip(
+    p0: (f32, f32),
+    p1: (f32, f32),
+    (width, height): (f32, f32),
+) -> Option<((f32, f32), (f32, f32))> {
+    if !(p0.0.is_finite()
+        && p0.1.is_finite()
+        && p1.0.is_finite()
+        && p1.1.is_finite())
+    {
+        return None;
+    }
+    let (min_x, max_x) = (-1.0, width + 1.0);
+    let (min_y, max_y) = (-1.0, height + 1.0);
+    let inside = |(x, y): (f32, f32)| {
+        x >= min_x && x <= max_x && y >= min_y && y <= max_y
+    };
+    if inside(p0) && inside(p1) {
+        return Some((p0, p1));
+    }
+    // Liang-Barsky
+    let (dx, dy) = (p1.0 - p0.0, p1.1 - p0.1);
+    let mut t0: f32 = 0.0;
+    let mut t1: f32 = 1.0;
+    for (p, q) in [
+        (-dx, p0.0 - min_x),
+        (dx, max_x - p0.0),
+        (-dy, p0.1 - min_y),
+        (dy, max_y - p0.1),
+    ] {
+        if p == 0.0 {
+            if q < 0.0 {
+                return None;
+            }
+        } else {
+            let r = q / p;
+            if p < 0.0 {
+                t0 = t0.max(r);
+            } else {
+                t1 = t1.min(r);
+            }
+        }
+    }
+    if t0 > t1 {
+        return None;
+    }
+    Some((
+        (p0.0 + t0 * dx, p0.1 + t0 * dy),
+        (p0.0 + t1 * dx, p0.1 + t1 * dy),
+    ))
+}
+
+/// the location in fixed point, with `SUBDOT_BITS` fractional bits
+fn to_fixed((x, y): (f32, f32)) -> (i64, i64) {
+    let one = (1i64 << SUBDOT_BITS) as f32;
+    ((x * one).round() as i64, (y * one).round() as i64)
+}
+
+/// The dots of the line between 2 points in fixed point dot space.
+/// This is Bresenham's algorithm generalized to end points which are not
+/// at the centers of the dots: along the major axis, the line is sampled
+/// at the center of each dot and the dot containing the exact point on
+/// the line is set, computed with integer arithmetic.
+/// The result only depends on the end points and not on which one is
+/// the start.
+pub(crate) fn line_dots(p0: (i64, i64), p1: (i64, i64)) -> Vec<(i64, i64)> {
+    let swap = |(x, y): (i64, i64)| (y, x);
+    if (p1.0 - p0.0).abs() >= (p1.1 - p0.1).abs() {
+        trace(p0, p1)
+    } else {
+        trace(swap(p0), swap(p1)).into_iter().map(swap).collect()
+    }
+}
+
+/// line_dots where x is the major axis
+fn trace(p0: (i64, i64), p1: (i64, i64)) -> Vec<(i64, i64)> {
+    let one = 1i64 << SUBDOT_BITS;
+    let ((x0, y0), (x1, y1)) = if p0 <= p1 { (p0, p1) } else { (p1, p0) };
+    let (dx, dy) = (x1 - x0, y1 - y0);
+    (x0.div_euclid(one)..=x1.div_euclid(one))
+        .map(|i| {
+            let x = (i * one + one / 2).clamp(x0, x1);
+            let y = if dx == 0 {
+                y0
+            } else {
+                y0 + ((x - x0) * dy).div_euclid(dx)
+            };
+            (i, y.div_euclid(one))
+        })
+        .collect()
+}
+
+/// The dots of an axis aligned ellipse using the midpoint ellipse
+/// algorithm with integer arithmetic.
+/// The center and radius are in half dots, so the ellipse can be centered
+/// at the center or the corner of a dot and still be symmetric.
+/// Each dot is returned once.
+pub(crate) fn midpoint_ellipse(
+    (cx, cy): (i64, i64),
+    (rx, ry): (i64, i64),
+) -> Vec<(i64, i64)> {
+    // the dot whose center is at the offset from the center
+    let dot = |x: i64, y: i64| {
+        ((cx + x - 1).div_euclid(2), (cy + y - 1).div_euclid(2))
+    };
+    if rx == 0 || ry == 0 {
+        let one = 1i64 << (SUBDOT_BITS - 1);
+        return line_dots(
+            ((cx - rx) * one, (cy - ry) * one),
+            ((cx + rx) * one, (cy + ry) * one),
+        );
+    }
+    let (rx2, ry2) = (rx as i128 * rx as i128, ry as i128 * ry as i128);
+    let f = |x: i64, y: i64| {
+        ry2 * (x as i128).pow(2) + rx2 * (y as i128).pow(2) - rx2 * ry2
+    };
+    // the offsets to the centers of the dots are odd when the center is at
+    // the corner of a dot, and even when it is at the center of a dot
+    let (px, py) = ((cx + 1).rem_euclid(2), (cy + 1).rem_euclid(2));
+    let mut x = px;
+    let mut y = if (ry - py) % 2 == 0 { ry } else { ry - 1 };
+    let mut quadrant = vec![];
+    // where the slope is less than 1, step along x
+    while ry2 * (x as i128) < rx2 * (y as i128) {
+        quadrant.push((x, y));
+        if f(x + 2, y - 1) > 0 {
+            y -= 2;
+        }
+        x += 2;
+    }
+    // the rest of the quadrant steps along y
+    while y >= py {
+        quadrant.push((x, y));
+        if f(x + 1, y - 2) < 0 {
+            x += 2;
+        }
+        y -= 2;
+    }
+    let mut seen = HashSet::new();
+    let mut dots = vec![];
+    for (x, y) in quadrant {
+        for (sx, sy) in [(1, 1), (-1, 1), (-1, -1), (1, -1)] {
+            let dot = dot(sx * x, sy * y);
+            if seen.insert(dot) {
+                dots.push(dot);
+            }
+        }
+    }
+    dots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn line_dots_are_unique_and_symmetric() {
+        let one = 1 << SUBDOT_BITS;
+        let lines = [((0, 0), (37, 11)), ((5, 40), (17, 3)), ((3, 3), (3, 3))];
+        for &((x0, y0), (x1, y1)) in lines.iter() {
+            let p0 = (x0 * one + 100, y0 * one + 7);
+            let p1 = (x1 * one + 3, y1 * one + 200);
+            let forward = line_dots(p0, p1);
+            let mut backward = line_dots(p1, p0);
+            backward.sort();
+            let mut sorted = forward.clone();
+            sorted.sort();
+            sorted.dedup();
+            assert_eq!(sorted.len(), forward.len());
+            assert_eq!(sorted, backward);
+            assert!(forward.windows(2).all(|pair| {
+                (pair[0].0 - pair[1].0).abs() <= 1
+                    && (pair[0].1 - pair[1].1).abs() <= 1
+            }));
+        }
+    }
+
+    #[test]
+    fn midpoint_circle_is_symmetric() {
+        // centered at the corner of a dot and at the center of a dot
+        for &center in &[(40, 40), (41, 41)] {
+            let dots = midpoint_ellipse(center, (30, 30));
+            for &(x, y) in dots.iter() {
+                let mirror = (center.0 - 1 - x, y);
+                assert!(dots.contains(&mirror), "{:?}", mirror);
+                let transposed = (y, x);
+                assert!(dots.contains(&transposed), "{:?}", transposed);
+            }
+        }
+    }
+
+    #[test]
+    fn drawing_direction_does_not_matter() {
+        let mut forward = Context::new(5.0, 2.0);
+        forward.draw(&Line::new(0.3, 0.1, 4.6, 1.9));
+        let mut backward = Context::new(5.0, 2.0);
+        backward.draw(&Line::new(4.6, 1.9, 0.3, 0.1));
+        assert_eq!(forward.to_string(), backward.to_string());
+    }
+}
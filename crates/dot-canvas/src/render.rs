@@ -0,0 +1,144 @@
+//! Incremental rendering of a `Grid` to the terminal, which only redraws
+//! the cells that changed since the previous frame
+use crate::Color;
+use crate::ColorMode;
+use crate::Grid;
+use std::io;
+use std::io::Write;
+
+/// ansi escape sequence to reset the color back to the terminal default
+const RESET: &str = "\x1b[0m";
+
+/// Draws the frames of a grid at a fixed location of the screen, keeping
+/// the previous frame to emit only the changed cells
+pub struct Renderer {
+    /// the screen row of the top edge of the grid, starting at 0
+    row: usize,
+    /// the screen column of the left edge of the grid, starting at 0
+    column: usize,
+    mode: ColorMode,
+    /// the width of the previous frame in cells
+    width: usize,
+    /// the character and color of each cell of the previous frame
+    previous: Option<Vec<(char, Option<Color>)>>,
+}
+
+impl Renderer {
+    /// A renderer which draws the grid with its top left cell at the
+    /// screen `row` and `column`, both starting at 0
+    pub fn new(row: usize, column: usize, mode: ColorMode) -> Self {
+        Renderer {
+            row,
+            column,
+            mode,
+            width: 0,
+            previous: None,
+        }
+    }
+
+    /// Move the grid to another location of the screen, which redraws the
+    /// whole grid on the next frame
+    pub fn set_offset(&mut self, row: usize, column: usize) {
+        self.row = row;
+        self.column = column;
+        self.invalidate();
+    }
+
+    /// Forget the previous frame, so the next frame is drawn in full, such
+    /// as after the screen was cleared
+    pub fn invalidate(&mut self) {
+        self.previous = None;
+    }
+
+    /// Write the escape sequences which update the screen from the previous
+    /// frame to this grid
+    pub fn render<W: Write>(
+        &mut self,
+        grid: &Grid,
+        writer: &mut W,
+    ) -> io::Result<()> {
+        let width = grid.width();
+        let frame: Vec<(char, Option<Color>)> = (0..grid.height())
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                let ch = grid.char_at(x, y);
+                // the color of an empty cell is not visible
+                let color = if ch == ' ' { None } else { grid.color(x, y) };
+                (ch, color)
+            })
+            .collect();
+        let previous = match self.previous.take() {
+            Some(previous)
+                if self.width == width && previous.len() == frame.len() =>
+            {
+                previous
+            }
+            _ => vec![],
+        };
+
+        let mut buf = String::new();
+        // the cell the cursor is at after the last written character
+        let mut cursor: Option<usize> = None;
+        let mut current: Option<Color> = None;
+        for (i, cell) in frame.iter().enumerate() {
+            if previous.get(i) == Some(cell) {
+                continue;
+            }
+            if cursor != Some(i) || i % width == 0 {
+                buf.push_str(&format!(
+                    "\x1b[{};{}H",
+                    self.row + i / width + 1,
+                    self.column + i % width + 1
+                ));
+            }
+            let (ch, color) = cell;
+            if *color != current {
+                match color {
+                    Some(color) => buf.push_str(&color.fg_escape(self.mode)),
+                    None => buf.push_str(RESET),
+                }
+                current = *color;
+            }
+            buf.push(*ch);
+            cursor = Some(i + 1);
+        }
+        if current.is_some() {
+            buf.push_str(RESET);
+        }
+        self.width = width;
+        self.previous = Some(frame);
+        writer.write_all(buf.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    fn render(renderer: &mut Renderer, context: &Context) -> String {
+        let mut out = vec![];
+        renderer.render(context.grid(), &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn render_changed_cells() {
+        let mut renderer = Renderer::new(2, 4, ColorMode::Ansi16);
+        let mut context = Context::new(2.0, 2.0);
+        context.draw(&Line::new(0.0, 0.0, 2.0, 0.0));
+        let result = render(&mut renderer, &context);
+        assert_eq!(result, "\x1b[3;5H⠉⠉⠉⠉\x1b[4;5H    ");
+
+        assert_eq!(render(&mut renderer, &context), "");
+
+        context.draw_with_color(&Dot { x: 1.25, y: 1.5 }, Color::RED);
+        context.draw(&Dot { x: 1.75, y: 1.5 });
+        let result = render(&mut renderer, &context);
+        assert_eq!(result, "\x1b[4;7H\x1b[31m⠠\x1b[0m⠠");
+
+        renderer.invalidate();
+        let result = render(&mut renderer, &context);
+        assert_eq!(result, "\x1b[3;5H⠉⠉⠉⠉\x1b[4;5H  \x1b[31m⠠\x1b[0m⠠");
+    }
+}
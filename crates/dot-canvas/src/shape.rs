@@ -1,6 +1,256 @@
+use crate::fill;
+use crate::Raster;
+
+/// the dot size used when the resolution of the canvas is not known
+pub(crate) const STEP: f32 = 0.25;
+
+/// the step, or `STEP` when it is not a positive finite number, so sampling
+/// with it always ends
+pub(crate) fn valid_step(step: f32) -> f32 {
+    if step.is_finite() && step > 0.0 {
+        step
+    } else {
+        STEP
+    }
+}
+
+/// the fraction of the size of a shape used as the dot size to flatten the
+/// outlines for the default queries
+const QUERY_TOLERANCE: f32 = 1.0 / 1024.0;
+
 pub trait Shape<'a> {
     /// Returns an iterator over all points of the shape
     fn points(&'a self) -> Box<dyn Iterator<Item = (f32, f32)> + 'a>;
+
+    /// Returns the points of the shape sampled for a canvas where a dot is
+    /// `dot_size` units wide, so curves can be drawn without gaps at any scale.
+    /// Defaults to `points` for shapes which don't depend on the resolution.
+    fn points_at_resolution(
+        &'a self,
+        _dot_size: f32,
+    ) -> Box<dyn Iterator<Item = (f32, f32)> + 'a> {
+        self.points()
+    }
+
+    /// Returns the outline of the shape as runs of connected vertices,
+    /// which is used for stroking the shape with a width and dashes.
+    /// Curves are flattened finely enough for a canvas where a dot is
+    /// `dot_size` units wide.
+    /// Defaults to each point as a single vertex for shapes without an
+    /// outline.
+    fn outlines(&'a self, dot_size: f32) -> Vec<Outline> {
+        self.points_at_resolution(dot_size)
+            .map(|p| Outline::open(vec![p]))
+            .collect()
+    }
+
+    /// Sets the dots of the shape in the integer dot space of the canvas,
+    /// which is used for drawing the shape with a single dot wide stroke.
+    /// Defaults to the lines of the outlines.
+    fn rasterize(&'a self, raster: &mut Raster) {
+        for outline in self.outlines(raster.dot_size()) {
+            raster.polyline(&outline.vertices, outline.closed);
+        }
+    }
+
+    /// Sets the dots inside the area of the shape, which are drawn in
+    /// addition to the stroke of the outlines at any stroke width.
+    /// Defaults to no dots, for shapes which are only outlined.
+    fn fill(&'a self, _raster: &mut Raster) {}
+
+    /// Returns the smallest axis aligned box containing the shape, None
+    /// for an empty shape.
+    /// Defaults to the box around the vertices of the outlines, which are
+    /// flattened again when the shape is small compared to `STEP`.
+    fn bounding_box(&'a self) -> Option<BoundingBox> {
+        let vertices = |outlines: Vec<Outline>| {
+            BoundingBox::from_points(
+                outlines
+                    .into_iter()
+                    .flat_map(|outline| outline.vertices.into_iter()),
+            )
+        };
+        let coarse = vertices(self.outlines(STEP));
+        match query_dot_size(coarse) {
+            dot_size if dot_size < STEP => vertices(self.outlines(dot_size)),
+            _ => coarse,
+        }
+    }
+
+    /// Returns the distance from the point (x, y) to the nearest point of
+    /// the outlines of the shape, infinity for an empty shape
+    fn distance(&'a self, x: f32, y: f32) -> f32 {
+        self.outlines(query_dot_size(self.bounding_box()))
+            .iter()
+            .map(|outline| outline_distance(outline, (x, y)))
+            .fold(f32::INFINITY, f32::min)
+    }
+
+    /// Returns whether the point (x, y) is inside the area enclosed by the
+    /// closed outlines, with the non-zero fill rule.
+    /// Shapes without closed outlines, like lines, contain no points.
+    fn contains(&'a self, x: f32, y: f32) -> bool {
+        let winding: i32 = self
+            .outlines(query_dot_size(self.bounding_box()))
+            .iter()
+            .filter(|outline| outline.closed)
+            .map(|outline| fill::winding(&outline.vertices, (x, y)))
+            .sum();
+        winding != 0
+    }
+}
+
+/// the dot size used to flatten the outlines of a shape with the bounding
+/// box for the default queries, or `STEP` for a shape without a size
+fn query_dot_size(bounding_box: Option<BoundingBox>) -> f32 {
+    let size = bounding_box
+        .map_or(0.0, |b| (b.max_x - b.min_x).max(b.max_y - b.min_y));
+    if size.is_normal() {
+        size * QUERY_TOLERANCE
+    } else {
+        STEP
+    }
+}
+
+/// The distance from the point p to the lines of the outline, or to its
+/// vertex when it has a single one, infinity for an empty outline
+pub(crate) fn outline_distance(outline: &Outline, p: (f32, f32)) -> f32 {
+    let vertices = &outline.vertices;
+    if vertices.len() == 1 {
+        return segment_distance(vertices[0], vertices[0], p);
+    }
+    let closing = if outline.closed {
+        vertices.first()
+    } else {
+        None
+    };
+    let ends = vertices.iter().skip(1).chain(closing);
+    vertices
+        .iter()
+        .zip(ends)
+        .map(|(p0, p1)| segment_distance(*p0, *p1, p))
+        .fold(f32::INFINITY, f32::min)
+}
+
+/// the distance from the point p to the line segment from p0 to p1
+fn segment_distance(p0: (f32, f32), p1: (f32, f32), p: (f32, f32)) -> f32 {
+    let (dx, dy) = (p1.0 - p0.0, p1.1 - p0.1);
+    let length_squared = dx * dx + dy * dy;
+    let t = if length_squared > 0.0 {
+        (((p.0 - p0.0) * dx + (p.1 - p0.1) * dy) / length_squared)
+            .clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let (nx, ny) = (p0.0 + t * dx, p0.1 + t * dy);
+    ((p.0 - nx).powi(2) + (p.1 - ny).powi(2)).sqrt()
+}
+
+/// An axis aligned rectangle which contains a shape
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    pub min_x: f32,
+    pub min_y: f32,
+    pub max_x: f32,
+    pub max_y: f32,
+}
+
+impl BoundingBox {
+    /// the box containing the whole plane
+    pub(crate) const UNBOUNDED: BoundingBox = BoundingBox {
+        min_x: f32::NEG_INFINITY,
+        min_y: f32::NEG_INFINITY,
+        max_x: f32::INFINITY,
+        max_y: f32::INFINITY,
+    };
+
+    pub fn new(min_x: f32, min_y: f32, max_x: f32, max_y: f32) -> Self {
+        BoundingBox {
+            min_x,
+            min_y,
+            max_x,
+            max_y,
+        }
+    }
+
+    /// the smallest box containing the finite points, None when there are
+    /// none
+    pub fn from_points<I>(points: I) -> Option<Self>
+    where
+        I: IntoIterator<Item = (f32, f32)>,
+    {
+        points
+            .into_iter()
+            .filter(|(x, y)| x.is_finite() && y.is_finite())
+            .map(|(x, y)| BoundingBox::new(x, y, x, y))
+            .reduce(|a, b| a.union(&b))
+    }
+
+    /// the smallest box containing both boxes
+    pub fn union(&self, other: &BoundingBox) -> Self {
+        BoundingBox::new(
+            self.min_x.min(other.min_x),
+            self.min_y.min(other.min_y),
+            self.max_x.max(other.max_x),
+            self.max_y.max(other.max_y),
+        )
+    }
+
+    /// Grow the box by the margin on every side
+    pub fn expand(&self, margin: f32) -> Self {
+        BoundingBox::new(
+            self.min_x - margin,
+            self.min_y - margin,
+            self.max_x + margin,
+            self.max_y + margin,
+        )
+    }
+
+    /// whether the boxes overlap, including touching edges
+    pub fn intersects(&self, other: &BoundingBox) -> bool {
+        self.min_x <= other.max_x
+            && other.min_x <= self.max_x
+            && self.min_y <= other.max_y
+            && other.min_y <= self.max_y
+    }
+
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        x >= self.min_x && x <= self.max_x && y >= self.min_y && y <= self.max_y
+    }
+
+    /// the corners of the box, which are used to transform it
+    pub fn corners(&self) -> [(f32, f32); 4] {
+        [
+            (self.min_x, self.min_y),
+            (self.max_x, self.min_y),
+            (self.max_x, self.max_y),
+            (self.min_x, self.max_y),
+        ]
+    }
+}
+
+/// A run of connected vertices of the outline of a shape
+#[derive(Debug, Clone, PartialEq)]
+pub struct Outline {
+    pub vertices: Vec<(f32, f32)>,
+    /// whether the last vertex connects back to the first vertex
+    pub closed: bool,
+}
+
+impl Outline {
+    pub fn open(vertices: Vec<(f32, f32)>) -> Self {
+        Outline {
+            vertices,
+            closed: false,
+        }
+    }
+
+    pub fn closed(vertices: Vec<(f32, f32)>) -> Self {
+        Outline {
+            vertices,
+            closed: true,
+        }
+    }
 }
 
 pub struct Dot {
@@ -12,4 +262,68 @@ impl<'a> Shape<'a> for Dot {
     fn points(&'a self) -> Box<dyn Iterator<Item = (f32, f32)> + 'a> {
         Box::new(std::iter::once((self.x, self.y)))
     }
+
+    fn outlines(&'a self, _dot_size: f32) -> Vec<Outline> {
+        vec![Outline::open(vec![(self.x, self.y)])]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+    use std::f32::consts::FRAC_1_SQRT_2;
+    use std::f32::consts::SQRT_2;
+
+    #[test]
+    fn bounding_boxes() {
+        let line = Line::new(3.0, 1.0, -1.0, 2.0);
+        assert_eq!(
+            line.bounding_box(),
+            Some(BoundingBox::new(-1.0, 1.0, 3.0, 2.0))
+        );
+        let circle = Circle::new(1.0, 1.0, 2.0);
+        assert_eq!(
+            circle.bounding_box(),
+            Some(BoundingBox::new(-1.0, -1.0, 3.0, 3.0))
+        );
+        let rotated = Transformed::new(
+            Rectangle::new(0.0, 0.0, 1.0, 1.0),
+            Transform::rotate(45.0),
+        );
+        let bbox = rotated.bounding_box().unwrap();
+        assert!((bbox.min_x + FRAC_1_SQRT_2).abs() < 1e-3);
+        assert!((bbox.max_y - SQRT_2).abs() < 1e-3);
+        assert_eq!(Polyline::new(&[]).bounding_box(), None);
+    }
+
+    #[test]
+    fn distance_and_contains() {
+        let rect = Rectangle::new(0.0, 0.0, 2.0, 1.0);
+        assert_eq!(rect.distance(1.0, 3.0), 2.0);
+        assert_eq!(rect.distance(1.0, 0.75), 0.25);
+        assert!(rect.contains(1.0, 0.5));
+        assert!(!rect.contains(3.0, 0.5));
+
+        let line = Line::new(0.0, 0.0, 2.0, 0.0);
+        assert_eq!(line.distance(3.0, 0.0), 1.0);
+        assert!(!line.contains(1.0, 0.0));
+
+        let circle = Circle::new(0.0, 0.0, 1.0);
+        assert!((circle.distance(2.0, 0.0) - 1.0).abs() < 0.01);
+        assert!(circle.contains(0.5, 0.5));
+        assert!(!circle.contains(0.9, 0.9));
+
+        let filled = FilledCircle::new(0.0, 0.0, 1.0);
+        assert!(filled.contains(0.5, 0.5));
+        let moved = Transformed::new(filled, Transform::translate(5.0, 0.0));
+        assert!(moved.contains(5.5, 0.5));
+        assert!(!moved.contains(0.5, 0.5));
+    }
+
+    #[test]
+    fn small_shapes_are_queried_at_their_scale() {
+        let ellipse = Ellipse::new(0.0, 0.0, 0.1, 0.05);
+        assert!((ellipse.distance(0.0, 0.2) - 0.15).abs() < 1e-3);
+    }
 }
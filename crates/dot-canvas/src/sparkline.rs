@@ -0,0 +1,142 @@
+//! A sparkline of the latest samples of a stream of values
+use crate::Context;
+use crate::Dot;
+use crate::Orientation;
+use crate::Polyline;
+use std::collections::VecDeque;
+
+/// Keeps the latest `capacity` samples and draws them as a line, with a
+/// column of dots for each sample
+pub struct Sparkline {
+    samples: VecDeque<f32>,
+    capacity: usize,
+    /// the fixed range of the values, None scales to the samples shown
+    range: Option<(f32, f32)>,
+}
+
+impl Sparkline {
+    pub fn new(capacity: usize) -> Self {
+        Sparkline {
+            samples: VecDeque::with_capacity(capacity),
+            capacity,
+            range: None,
+        }
+    }
+
+    /// Add a sample, dropping the oldest sample when the buffer is full.
+    /// Samples which are not finite leave a gap in the line.
+    pub fn push(&mut self, sample: f32) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    /// the samples from the oldest to the latest
+    pub fn samples(&self) -> impl Iterator<Item = f32> + '_ {
+        self.samples.iter().copied()
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+
+    /// Scale the line to the fixed range of values, samples outside of it
+    /// are clamped
+    pub fn set_range(&mut self, min: f32, max: f32) {
+        self.range = Some((min, max));
+    }
+
+    /// Scale the line to the smallest and largest sample shown
+    pub fn autoscale(&mut self) {
+        self.range = None;
+    }
+
+    /// Draw the latest samples that fit into the context, aligned to the
+    /// right edge, which has its bounds set to the dots of the grid
+    pub fn draw(&self, context: &mut Context) {
+        let (dots_wide, dots_high) = context.grid().resolution();
+        context.set_x_bounds(0.0, dots_wide as f32);
+        context.set_y_bounds(0.0, dots_high as f32);
+        context.set_orientation(Orientation::YUp);
+
+        let shown = self.samples.len().min(dots_wide);
+        let samples = self.samples.iter().skip(self.samples.len() - shown);
+        let (min, max) =
+            match self.range {
+                Some(range) => range,
+                None => samples.clone().filter(|s| s.is_finite()).fold(
+                    (f32::INFINITY, f32::NEG_INFINITY),
+                    |(min, max), s| (min.min(*s), max.max(*s)),
+                ),
+            };
+        let points: Vec<(f32, f32)> = samples
+            .enumerate()
+            .map(|(i, sample)| {
+                let x = (dots_wide - shown + i) as f32 + 0.5;
+                if !sample.is_finite() {
+                    return (x, f32::NAN);
+                }
+                // a flat line is drawn at the bottom
+                let t = if max > min {
+                    ((sample - min) / (max - min)).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                };
+                (x, t * (dots_high as f32 - 1.0) + 0.5)
+            })
+            .collect();
+        for run in points.split(|(_, y)| !y.is_finite()) {
+            match run {
+                [] => (),
+                [(x, y)] => context.draw(&Dot { x: *x, y: *y }),
+                _ => context.draw(&Polyline::new(run)),
+            }
+        }
+    }
+
+    /// Render the latest samples into a single line of `width` braille
+    /// characters
+    pub fn render(&self, width: usize) -> String {
+        let mut context =
+            Context::with_bounds(width, 1, (0.0, 1.0), (0.0, 1.0));
+        self.draw(&mut context);
+        context.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ring_buffer() {
+        let mut sparkline = Sparkline::new(3);
+        for i in 0..5 {
+            sparkline.push(i as f32);
+        }
+        assert_eq!(
+            sparkline.samples().collect::<Vec<_>>(),
+            vec![2.0, 3.0, 4.0]
+        );
+    }
+
+    #[test]
+    fn render_sparkline() {
+        let mut sparkline = Sparkline::new(16);
+        for sample in [0.0, 1.0, 3.0, 2.0, 3.0, f32::NAN, 1.0, 0.0, 2.0] {
+            sparkline.push(sample);
+        }
+        let result = sparkline.render(5);
+        println!("{}", result);
+        assert_eq!(result, "⢀⠜⠊⠠⡰");
+
+        sparkline.set_range(0.0, 6.0);
+        let result = sparkline.render(3);
+        println!("{}", result);
+        assert_eq!(result, "⠤⢀⡠");
+    }
+}
@@ -0,0 +1,612 @@
+use crate::fill::scanline_fill;
+use crate::BoundingBox;
+use crate::FillRule;
+use crate::FilledCircle;
+use crate::Outline;
+use crate::Shape;
+use std::collections::HashSet;
+use std::f32::consts::SQRT_2;
+
+/// dash patterns shorter than this many dots are drawn as a solid stroke,
+/// as their dashes and gaps can't be told apart
+const MIN_DASH_PERIOD: f32 = 1.0;
+
+/// The shape at the ends of an open stroke
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineCap {
+    /// the stroke ends exactly at the end point
+    Butt,
+    /// a half circle is added at the end point
+    Round,
+    /// the stroke extends past the end point by half of its width
+    Square,
+}
+
+/// The shape at the corners where 2 segments of a stroke meet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineJoin {
+    /// the outer edges are extended until they meet, limited by the miter
+    /// limit
+    Miter,
+    /// the corner is rounded
+    Round,
+    /// the corner is cut off
+    Bevel,
+}
+
+/// How the outline of the shapes are drawn.
+/// The lengths are measured in dots, so the stroke looks the same at any
+/// world bounds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StrokeStyle {
+    /// the width of the stroke in dots
+    pub width: f32,
+    /// alternating lengths of the dashes and gaps in dots, an empty array
+    /// draws a solid stroke
+    pub dash_array: Vec<f32>,
+    /// how far into the dash pattern the stroke starts, in dots
+    pub dash_offset: f32,
+    pub line_cap: LineCap,
+    pub line_join: LineJoin,
+    /// the maximum ratio of the miter length to half of the stroke width,
+    /// beyond which a bevel join is used
+    pub miter_limit: f32,
+}
+
+impl Default for StrokeStyle {
+    fn default() -> Self {
+        StrokeStyle {
+            width: 1.0,
+            dash_array: vec![],
+            dash_offset: 0.0,
+            line_cap: LineCap::Butt,
+            line_join: LineJoin::Miter,
+            miter_limit: 4.0,
+        }
+    }
+}
+
+impl StrokeStyle {
+    /// a solid stroke with the width in dots
+    pub fn new(width: f32) -> Self {
+        StrokeStyle {
+            width,
+            ..Default::default()
+        }
+    }
+
+    /// the dash pattern to use, None for a solid stroke.
+    /// Odd number of lengths are repeated to make it even, as in svg
+    fn dashes(&self) -> Option<Vec<f32>> {
+        let total: f32 = self.dash_array.iter().sum();
+        if !total.is_finite()
+            || total < MIN_DASH_PERIOD
+            || self.dash_array.iter().any(|d| *d < 0.0)
+        {
+            None
+        } else if self.dash_array.len() % 2 == 1 {
+            Some(self.dash_array.repeat(2))
+        } else {
+            Some(self.dash_array.clone())
+        }
+    }
+
+    /// A single dot wide solid stroke, which is drawn with the points of
+    /// the shape directly
+    pub(crate) fn is_hairline(&self) -> bool {
+        self.width <= 1.0 && self.dashes().is_none()
+    }
+
+    /// how far in dots the stroke reaches out from the outline, including
+    /// the caps and the miters of the joins of wide strokes
+    pub(crate) fn reach(&self) -> f32 {
+        if self.width <= 1.0 {
+            return 0.5;
+        }
+        let corner = match self.line_join {
+            LineJoin::Miter => self.miter_limit.max(SQRT_2),
+            _ => SQRT_2,
+        };
+        self.width / 2.0 * corner
+    }
+}
+
+fn distance(p0: (f32, f32), p1: (f32, f32)) -> f32 {
+    ((p1.0 - p0.0).powi(2) + (p1.1 - p0.1).powi(2)).sqrt()
+}
+
+fn lerp(p0: (f32, f32), p1: (f32, f32), t: f32) -> (f32, f32) {
+    (p0.0 + (p1.0 - p0.0) * t, p0.1 + (p1.1 - p0.1) * t)
+}
+
+/// split the polyline into the dashes of the pattern
+fn dash(
+    vertices: &[(f32, f32)],
+    dashes: &[f32],
+    offset: f32,
+) -> Vec<Vec<(f32, f32)>> {
+    let total: f32 = dashes.iter().sum();
+    let mut index = 0;
+    let mut remaining = dashes[0];
+    let mut offset = offset.rem_euclid(total);
+    while offset > 0.0 {
+        if offset >= remaining {
+            offset -= remaining;
+            index = (index + 1) % dashes.len();
+            remaining = dashes[index];
+        } else {
+            remaining -= offset;
+            offset = 0.0;
+        }
+    }
+    let mut on = index % 2 == 0;
+    let mut result = vec![];
+    let mut current = if on { vec![vertices[0]] } else { vec![] };
+    for pair in vertices.windows(2) {
+        let (p0, p1) = (pair[0], pair[1]);
+        let length = distance(p0, p1);
+        let mut t = 0.0;
+        // the dash or gap ends within this segment
+        while length - t > remaining {
+            t += remaining;
+            let p = lerp(p0, p1, t / length);
+            if on {
+                current.push(p);
+                result.push(std::mem::take(&mut current));
+            } else {
+                current = vec![p];
+            }
+            on = !on;
+            index = (index + 1) % dashes.len();
+            remaining = dashes[index];
+        }
+        remaining -= length - t;
+        if on {
+            current.push(p1);
+        }
+    }
+    if on && !current.is_empty() {
+        result.push(current);
+    }
+    result
+}
+
+/// Points along a dash of a single dot wide stroke.
+/// The points are at the middle of each dot length, so a dash ending exactly
+/// where the gap begins doesn't light the dot of the gap.
+fn sample_dash(vertices: &[(f32, f32)], dot_size: f32) -> Vec<(f32, f32)> {
+    let lengths: Vec<f32> = vertices
+        .windows(2)
+        .map(|pair| distance(pair[0], pair[1]))
+        .collect();
+    let total: f32 = lengths.iter().sum();
+    let n = (total / dot_size).round().max(1.0) as usize;
+    let mut points = vec![];
+    let mut segment = 0;
+    let mut start = 0.0;
+    for i in 0..n {
+        let s = (i as f32 + 0.5) * total / n as f32;
+        while segment + 1 < lengths.len() && s > start + lengths[segment] {
+            start += lengths[segment];
+            segment += 1;
+        }
+        match lengths.get(segment) {
+            Some(&length) if length > 0.0 => points.push(lerp(
+                vertices[segment],
+                vertices[segment + 1],
+                (s - start) / length,
+            )),
+            _ => points.push(vertices[segment]),
+        }
+    }
+    points
+}
+
+/// The fractions of the segment from p0 to p1 where it enters and leaves
+/// the box, None if it misses the box
+fn clip_segment(
+    p0: (f32, f32),
+    p1: (f32, f32),
+    clip: &BoundingBox,
+) -> Option<(f32, f32)> {
+    let (dx, dy) = (p1.0 - p0.0, p1.1 - p0.1);
+    let (mut t0, mut t1) = (0.0f32, 1.0f32);
+    let edges = [
+        (-dx, p0.0 - clip.min_x),
+        (dx, clip.max_x - p0.0),
+        (-dy, p0.1 - clip.min_y),
+        (dy, clip.max_y - p0.1),
+    ];
+    for (p, q) in edges {
+        if p == 0.0 {
+            if q < 0.0 {
+                return None;
+            }
+        } else if p < 0.0 {
+            t0 = t0.max(q / p);
+        } else {
+            t1 = t1.min(q / p);
+        }
+    }
+    if t0 <= t1 {
+        Some((t0, t1))
+    } else {
+        None
+    }
+}
+
+/// How far along a polyline a run of its vertices starts, the vertices and
+/// whether they are closed
+type Run = (f32, Vec<(f32, f32)>, bool);
+
+/// The runs of the polyline inside the box, with how far along the
+/// polyline each run starts and whether it is still closed.
+/// A closed polyline crossing the box becomes open runs, where the runs
+/// meeting at the first vertex are joined.
+fn clip_polyline(
+    vertices: &[(f32, f32)],
+    closed: bool,
+    clip: &BoundingBox,
+) -> Vec<Run> {
+    if vertices.iter().all(|(x, y)| clip.contains(*x, *y)) {
+        return vec![(0.0, vertices.to_vec(), closed)];
+    }
+    let closing = if closed { vertices.first() } else { None };
+    let ends = vertices.iter().skip(1).chain(closing);
+    let mut runs: Vec<Run> = vec![];
+    let mut length = 0.0;
+    // whether the last run ends at the start of the segment
+    let mut connected = false;
+    for (&p0, &p1) in vertices.iter().zip(ends) {
+        let segment = distance(p0, p1);
+        let finite = [p0.0, p0.1, p1.0, p1.1].iter().all(|v| v.is_finite());
+        match clip_segment(p0, p1, clip).filter(|_| finite) {
+            Some((t0, t1)) => {
+                let a = if t0 > 0.0 { lerp(p0, p1, t0) } else { p0 };
+                let b = if t1 < 1.0 { lerp(p0, p1, t1) } else { p1 };
+                match runs.last_mut() {
+                    Some(run) if connected && t0 == 0.0 => run.1.push(b),
+                    _ => runs.push((length + t0 * segment, vec![a, b], false)),
+                }
+                connected = t1 == 1.0;
+            }
+            None => connected = false,
+        }
+        length += segment;
+    }
+    // the closed polyline continues from the last run into the first run
+    if closed && connected && runs.len() > 1 && runs[0].0 == 0.0 {
+        let first = runs.remove(0);
+        if let Some(last) = runs.last_mut() {
+            last.1.extend_from_slice(&first.1[1..]);
+        }
+    }
+    runs
+}
+
+/// Converts strokes into filled polygons and discs
+struct Stroker<'s> {
+    style: &'s StrokeStyle,
+    half_width: f32,
+    polygons: Vec<Vec<(f32, f32)>>,
+    discs: Vec<(f32, f32)>,
+}
+
+impl<'s> Stroker<'s> {
+    fn point(&mut self, p: (f32, f32)) {
+        match self.style.line_cap {
+            LineCap::Butt => (),
+            LineCap::Round => self.discs.push(p),
+            LineCap::Square => {
+                let hw = self.half_width;
+                self.polygons.push(vec![
+                    (p.0 - hw, p.1 - hw),
+                    (p.0 + hw, p.1 - hw),
+                    (p.0 + hw, p.1 + hw),
+                    (p.0 - hw, p.1 + hw),
+                ]);
+            }
+        }
+    }
+
+    fn join(&mut self, p0: (f32, f32), v: (f32, f32), p1: (f32, f32)) {
+        let hw = self.half_width;
+        let (la, lb) = (distance(p0, v), distance(v, p1));
+        let ua = ((v.0 - p0.0) / la, (v.1 - p0.1) / la);
+        let ub = ((p1.0 - v.0) / lb, (p1.1 - v.1) / lb);
+        let cross = ua.0 * ub.1 - ua.1 * ub.0;
+        if cross == 0.0 && ua.0 * ub.0 + ua.1 * ub.1 > 0.0 {
+            return;
+        }
+        if self.style.line_join == LineJoin::Round {
+            self.discs.push(v);
+            return;
+        }
+        // the normals pointing to the outer side of the corner
+        let sign = if cross > 0.0 { -1.0 } else { 1.0 };
+        let na = (-ua.1 * sign, ua.0 * sign);
+        let nb = (-ub.1 * sign, ub.0 * sign);
+        let a = (v.0 + na.0 * hw, v.1 + na.1 * hw);
+        let b = (v.0 + nb.0 * hw, v.1 + nb.1 * hw);
+        let m = (na.0 + nb.0, na.1 + nb.1);
+        let m_length = (m.0 * m.0 + m.1 * m.1).sqrt();
+        if self.style.line_join == LineJoin::Miter && m_length > 0.0 {
+            let m = (m.0 / m_length, m.1 / m_length);
+            let cos_half = m.0 * na.0 + m.1 * na.1;
+            let miter = 1.0 / cos_half;
+            if miter <= self.style.miter_limit {
+                let tip = (v.0 + m.0 * hw * miter, v.1 + m.1 * hw * miter);
+                self.polygons.push(vec![v, a, tip, b]);
+                return;
+            }
+        }
+        self.polygons.push(vec![v, a, b]);
+    }
+
+    fn polyline(&mut self, vertices: &[(f32, f32)], closed: bool) {
+        let mut vertices = vertices.to_vec();
+        vertices.dedup();
+        if closed && vertices.len() > 1 && vertices.first() == vertices.last() {
+            vertices.pop();
+        }
+        if vertices.len() == 1 {
+            self.point(vertices[0]);
+            return;
+        }
+        let hw = self.half_width;
+        let n = vertices.len();
+        let segment_count = if closed { n } else { n - 1 };
+        for i in 0..segment_count {
+            let (mut a, mut b) = (vertices[i], vertices[(i + 1) % n]);
+            let length = distance(a, b);
+            let u = ((b.0 - a.0) / length, (b.1 - a.1) / length);
+            if !closed && self.style.line_cap == LineCap::Square {
+                if i == 0 {
+                    a = (a.0 - u.0 * hw, a.1 - u.1 * hw);
+                }
+                if i == segment_count - 1 {
+                    b = (b.0 + u.0 * hw, b.1 + u.1 * hw);
+                }
+            }
+            let normal = (-u.1 * hw, u.0 * hw);
+            self.polygons.push(vec![
+                (a.0 + normal.0, a.1 + normal.1),
+                (b.0 + normal.0, b.1 + normal.1),
+                (b.0 - normal.0, b.1 - normal.1),
+                (a.0 - normal.0, a.1 - normal.1),
+            ]);
+        }
+        let joins = if closed { 0..n } else { 1..n - 1 };
+        for i in joins {
+            let prev = vertices[(i + n - 1) % n];
+            let next = vertices[(i + 1) % n];
+            self.join(prev, vertices[i], next);
+        }
+        if !closed && self.style.line_cap == LineCap::Round {
+            self.discs.push(vertices[0]);
+            self.discs.push(vertices[n - 1]);
+        }
+    }
+}
+
+/// The points to draw for the stroke of the outlines, where a dot is
+/// `dot_size` wide.
+/// Only the parts of the outlines which the stroke can reach the canvas from
+/// are stroked, so the work doesn't depend on how far the outlines extend
+/// outside of it.
+/// Each dot is only returned once, even where the pieces of the stroke
+/// overlap.
+pub(crate) fn stroke(
+    outlines: &[Outline],
+    style: &StrokeStyle,
+    dot_size: f32,
+    canvas: &BoundingBox,
+) -> Vec<(f32, f32)> {
+    let dashes = style.dashes();
+    let clip = canvas.expand((style.reach() + 1.0) * dot_size);
+    let mut pieces = vec![];
+    let mut points = vec![];
+    for outline in outlines {
+        match outline.vertices.len() {
+            0 => continue,
+            1 => {
+                points.push(outline.vertices[0]);
+                continue;
+            }
+            _ => (),
+        }
+        match &dashes {
+            Some(dashes) => {
+                let mut vertices = outline.vertices.clone();
+                if outline.closed {
+                    vertices.extend(outline.vertices.first());
+                }
+                let dashes: Vec<f32> =
+                    dashes.iter().map(|d| d * dot_size).collect();
+                for (start, vertices, _) in
+                    clip_polyline(&vertices, false, &clip)
+                {
+                    // the pattern continues where the run starts
+                    let offset = style.dash_offset * dot_size + start;
+                    pieces.extend(
+                        dash(&vertices, &dashes, offset)
+                            .into_iter()
+                            .map(|vertices| (vertices, false)),
+                    );
+                }
+            }
+            None => pieces.extend(
+                clip_polyline(&outline.vertices, outline.closed, &clip)
+                    .into_iter()
+                    .map(|(_, vertices, closed)| (vertices, closed)),
+            ),
+        }
+    }
+
+    if style.width <= 1.0 {
+        for (vertices, _) in pieces {
+            points.extend(sample_dash(&vertices, dot_size));
+        }
+        return points;
+    }
+
+    let half_width = style.width * dot_size / 2.0;
+    let mut stroker = Stroker {
+        style,
+        half_width,
+        polygons: vec![],
+        discs: points,
+    };
+    for (vertices, closed) in pieces {
+        stroker.polyline(&vertices, closed);
+    }
+    let filled = stroker
+        .polygons
+        .into_iter()
+        .flat_map(|polygon| {
+            scanline_fill(&[polygon], FillRule::NonZero, dot_size, clip)
+        })
+        .chain(stroker.discs.iter().flat_map(|&(x, y)| {
+            FilledCircle::new(x, y, half_width)
+                .points_at_resolution(dot_size)
+                .collect::<Vec<_>>()
+        }));
+    let mut seen = HashSet::new();
+    filled
+        .filter(|(x, y)| seen.insert((x.to_bits(), y.to_bits())))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    fn draw(style: StrokeStyle, shape: &Polyline) -> String {
+        let mut context = Context::new(4.0, 2.0);
+        context.set_stroke_style(style);
+        context.draw(shape);
+        let result = context.to_string();
+        println!("{}", result);
+        result
+    }
+
+    #[test]
+    fn wide_line() {
+        let line = Polyline::new(&[(0.5, 1.0), (3.5, 1.0)]);
+        let result = draw(StrokeStyle::new(4.0), &line);
+        let expected = [" ⣤⣤⣤⣤⣤⣤ ", " ⠛⠛⠛⠛⠛⠛ "];
+        assert_eq!(result, expected.join("\n"));
+    }
+
+    #[test]
+    fn square_cap_extends_the_line() {
+        let line = Polyline::new(&[(0.5, 1.0), (3.5, 1.0)]);
+        let style = StrokeStyle {
+            line_cap: LineCap::Square,
+            ..StrokeStyle::new(4.0)
+        };
+        let result = draw(style, &line);
+        let expected = ["⣤⣤⣤⣤⣤⣤⣤⣤", "⠛⠛⠛⠛⠛⠛⠛⠛"];
+        assert_eq!(result, expected.join("\n"));
+    }
+
+    #[test]
+    fn dashed_line() {
+        let line = Polyline::new(&[(0.0, 0.5), (4.0, 0.5)]);
+        let style = StrokeStyle {
+            dash_array: vec![3.0, 1.0],
+            dash_offset: 1.0,
+            ..StrokeStyle::default()
+        };
+        let result = draw(style, &line);
+        let expected = ["⠤⠠⠤⠠⠤⠠⠤⠠", "        "];
+        assert_eq!(result, expected.join("\n"));
+    }
+
+    #[test]
+    fn miter_and_bevel_joins() {
+        let corner = Polyline::new(&[(0.5, 1.75), (1.75, 0.5), (3.0, 1.75)]);
+        let style = StrokeStyle::new(3.0);
+        let result = draw(style.clone(), &corner);
+        let expected = [" ⢀⣴⣿⣦⡀  ", "⠐⣿⠟⠉⠻⡿⠂ "];
+        assert_eq!(result, expected.join("\n"));
+
+        let style = StrokeStyle {
+            line_join: LineJoin::Bevel,
+            ..style
+        };
+        let result = draw(style, &corner);
+        let expected = [" ⢀⣴⣶⣦⡀  ", "⠐⣿⠟⠉⠻⡿⠂ "];
+        assert_eq!(result, expected.join("\n"));
+    }
+
+    #[test]
+    fn dash_splits_polyline() {
+        let dashes =
+            dash(&[(0.0, 0.0), (4.0, 0.0), (4.0, 4.0)], &[3.0, 2.0], 0.0);
+        assert_eq!(
+            dashes,
+            vec![vec![(0.0, 0.0), (3.0, 0.0)], vec![(4.0, 1.0), (4.0, 4.0)],]
+        );
+    }
+
+    #[test]
+    fn empty_outline_is_skipped() {
+        let svg = Svg::parse(
+            r#"<svg><polyline points=""/><line x1="0" y1="0" x2="1" y2="1"/></svg>"#,
+        )
+        .unwrap();
+        let mut context = Context::new(1.0, 1.0);
+        context.set_stroke_style(StrokeStyle::new(3.0));
+        context.draw(&svg);
+        let dashed = StrokeStyle {
+            dash_array: vec![1.0, 1.0],
+            ..StrokeStyle::new(3.0)
+        };
+        context.set_stroke_style(dashed);
+        context.draw(&svg);
+        assert!(context.lit_dots().count() > 0);
+    }
+
+    #[test]
+    fn outlines_are_clipped_to_the_canvas() {
+        let long = Polyline::new(&[(-1e6, 1.0), (1e6, 1.0)]);
+        let result = draw(StrokeStyle::new(4.0), &long);
+        let expected = ["⣤⣤⣤⣤⣤⣤⣤⣤", "⠛⠛⠛⠛⠛⠛⠛⠛"];
+        assert_eq!(result, expected.join("\n"));
+
+        // the dashes are where they are without the part that is clipped
+        let dashed = StrokeStyle {
+            dash_array: vec![3.0, 1.0],
+            ..StrokeStyle::new(2.0)
+        };
+        let inside = Polyline::new(&[(0.0, 1.0), (4.0, 1.0)]);
+        let outside = Polyline::new(&[(-2000.0, 1.0), (4.0, 1.0)]);
+        assert_eq!(draw(dashed.clone(), &outside), draw(dashed, &inside));
+    }
+
+    #[test]
+    fn clip_closed_polyline() {
+        let clip = BoundingBox::new(0.0, 0.0, 2.0, 2.0);
+        let square = [(1.0, 1.0), (3.0, 1.0), (3.0, 3.0), (1.0, 3.0)];
+        let runs = clip_polyline(&square, true, &clip);
+        assert_eq!(
+            runs,
+            vec![(7.0, vec![(1.0, 2.0), (1.0, 1.0), (2.0, 1.0)], false)]
+        );
+        let inside = [(0.5, 0.5), (1.5, 0.5), (1.5, 1.5)];
+        let runs = clip_polyline(&inside, true, &clip);
+        assert_eq!(runs, vec![(0.0, inside.to_vec(), true)]);
+    }
+
+    #[test]
+    fn tiny_dashes_are_drawn_solid() {
+        let line = Polyline::new(&[(0.5, 1.0), (3.5, 1.0)]);
+        let tiny = StrokeStyle {
+            dash_array: vec![0.01, 0.01],
+            ..StrokeStyle::new(4.0)
+        };
+        assert_eq!(draw(tiny, &line), draw(StrokeStyle::new(4.0), &line));
+    }
+}
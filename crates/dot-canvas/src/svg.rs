@@ -0,0 +1,432 @@
+//! A minimal svg loader which converts the basic svg shape elements into
+//! shapes that can be drawn into a `Context`.
+//! Only the geometry is read, styles and transforms are ignored.
+use crate::BoundingBox;
+use crate::Circle;
+use crate::Line;
+use crate::Outline;
+use crate::Polygon;
+use crate::Polyline;
+use crate::Raster;
+use crate::Rectangle;
+use crate::Shape;
+use path::Parser;
+pub use path::Path;
+pub use path::Segment;
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+
+mod path;
+
+/// An error while parsing svg, with the byte position where it occurred
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub position: usize,
+    pub message: String,
+}
+
+/// An error while loading an svg file
+#[derive(Debug)]
+pub enum SvgError {
+    Io(io::Error),
+    Parse(ParseError),
+}
+
+/// The svg elements that are supported
+pub enum Element {
+    Line(Line),
+    Circle(Circle),
+    Rectangle(Rectangle),
+    Polyline(Polyline),
+    Polygon(Polygon),
+    Path(Path),
+}
+
+/// The shapes of an svg document
+pub struct Svg {
+    pub elements: Vec<Element>,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl fmt::Display for SvgError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SvgError::Io(e) => write!(f, "{}", e),
+            SvgError::Parse(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for SvgError {}
+
+impl From<io::Error> for SvgError {
+    fn from(e: io::Error) -> Self {
+        SvgError::Io(e)
+    }
+}
+
+impl From<ParseError> for SvgError {
+    fn from(e: ParseError) -> Self {
+        SvgError::Parse(e)
+    }
+}
+
+impl Svg {
+    /// load the shapes from a local svg file
+    pub fn load<P: AsRef<std::path::Path>>(path: P) -> Result<Self, SvgError> {
+        let content = std::fs::read_to_string(path)?;
+        Ok(Svg::parse(&content)?)
+    }
+
+    /// parse the shape elements of an svg document, other elements are
+    /// skipped
+    pub fn parse(svg: &str) -> Result<Self, ParseError> {
+        let mut elements = vec![];
+        let mut pos = 0;
+        while let Some(found) = svg[pos..].find('<') {
+            let start = pos + found;
+            let rest = &svg[start..];
+            if rest.starts_with("<!--") {
+                pos = start + find_end(rest, "-->", start)?;
+            } else if rest.starts_with("<?")
+                || rest.starts_with("<!")
+                || rest.starts_with("</")
+            {
+                pos = start + find_end(rest, ">", start)?;
+            } else {
+                let (tag, attributes, end) = parse_tag(svg, start)?;
+                if let Some(element) = to_element(tag, &attributes, start)? {
+                    elements.push(element);
+                }
+                pos = end;
+            }
+        }
+        Ok(Svg { elements })
+    }
+}
+
+/// the position right after the terminator
+fn find_end(
+    s: &str,
+    terminator: &str,
+    offset: usize,
+) -> Result<usize, ParseError> {
+    s.find(terminator)
+        .map(|i| i + terminator.len())
+        .ok_or_else(|| ParseError {
+            position: offset,
+            message: format!("unterminated tag, expecting `{}`", terminator),
+        })
+}
+
+/// An attribute value and its byte position in the document
+type Attributes<'a> = HashMap<&'a str, (&'a str, usize)>;
+
+/// Parse the tag starting at `start`, returning the tag name, its
+/// attributes and the position after the tag
+fn parse_tag(
+    svg: &str,
+    start: usize,
+) -> Result<(&str, Attributes<'_>, usize), ParseError> {
+    let bytes = svg.as_bytes();
+    let error = |position: usize, message: &str| ParseError {
+        position,
+        message: message.to_string(),
+    };
+    let is_name = |b: u8| {
+        b.is_ascii_alphanumeric() || b == b'-' || b == b':' || b == b'_'
+    };
+    let skip_whitespace = |mut pos: usize| {
+        while pos < bytes.len() && bytes[pos].is_ascii_whitespace() {
+            pos += 1;
+        }
+        pos
+    };
+    let name_end = |mut pos: usize| {
+        while pos < bytes.len() && is_name(bytes[pos]) {
+            pos += 1;
+        }
+        pos
+    };
+
+    let mut pos = start + 1;
+    let tag_end = name_end(pos);
+    if tag_end == pos {
+        return Err(error(pos, "expecting a tag name"));
+    }
+    let tag = &svg[pos..tag_end];
+    pos = tag_end;
+    let mut attributes = HashMap::new();
+    loop {
+        pos = skip_whitespace(pos);
+        match bytes.get(pos) {
+            None => return Err(error(start, "unterminated tag")),
+            Some(b'>') => return Ok((tag, attributes, pos + 1)),
+            Some(b'/') if bytes.get(pos + 1) == Some(&b'>') => {
+                return Ok((tag, attributes, pos + 2));
+            }
+            Some(_) => {
+                let end = name_end(pos);
+                if end == pos {
+                    return Err(error(pos, "expecting an attribute name"));
+                }
+                let name = &svg[pos..end];
+                pos = skip_whitespace(end);
+                if bytes.get(pos) != Some(&b'=') {
+                    return Err(error(pos, "expecting `=`"));
+                }
+                pos = skip_whitespace(pos + 1);
+                let quote = match bytes.get(pos) {
+                    Some(q @ b'"') | Some(q @ b'\'') => *q,
+                    _ => return Err(error(pos, "expecting a quoted value")),
+                };
+                let value_start = pos + 1;
+                let value_end = svg[value_start..]
+                    .find(quote as char)
+                    .map(|i| value_start + i)
+                    .ok_or_else(|| {
+                        error(pos, "unterminated attribute value")
+                    })?;
+                attributes
+                    .insert(name, (&svg[value_start..value_end], value_start));
+                pos = value_end + 1;
+            }
+        }
+    }
+}
+
+/// the number value of an attribute, missing attributes are 0 as in svg.
+/// A trailing `px` unit is allowed.
+fn number(attributes: &Attributes, name: &str) -> Result<f32, ParseError> {
+    match attributes.get(name) {
+        None => Ok(0.0),
+        Some((value, position)) => {
+            let value = value.trim();
+            let value = value.strip_suffix("px").unwrap_or(value);
+            value.parse().map_err(|_| ParseError {
+                position: *position,
+                message: format!("invalid number for `{}`", name),
+            })
+        }
+    }
+}
+
+fn points(
+    attributes: &Attributes,
+    name: &str,
+) -> Result<Vec<(f32, f32)>, ParseError> {
+    match attributes.get(name) {
+        None => Ok(vec![]),
+        Some((value, position)) => Parser::new(value, *position).points(),
+    }
+}
+
+fn to_element(
+    tag: &str,
+    attributes: &Attributes,
+    position: usize,
+) -> Result<Option<Element>, ParseError> {
+    let n = |name| number(attributes, name);
+    let element = match tag {
+        "line" => {
+            Element::Line(Line::new(n("x1")?, n("y1")?, n("x2")?, n("y2")?))
+        }
+        "circle" => Element::Circle(Circle::new(n("cx")?, n("cy")?, n("r")?)),
+        "rect" => {
+            let (x, y) = (n("x")?, n("y")?);
+            Element::Rectangle(Rectangle::new(
+                x,
+                y,
+                x + n("width")?,
+                y + n("height")?,
+            ))
+        }
+        "polyline" => Element::Polyline(Polyline {
+            points: points(attributes, "points")?,
+        }),
+        "polygon" => Element::Polygon(Polygon {
+            points: points(attributes, "points")?,
+        }),
+        "path" => match attributes.get("d") {
+            Some((d, offset)) => Element::Path(Parser::new(d, *offset).path()?),
+            None => {
+                return Err(ParseError {
+                    position,
+                    message: "path is missing the `d` attribute".to_string(),
+                })
+            }
+        },
+        _ => return Ok(None),
+    };
+    Ok(Some(element))
+}
+
+impl<'a> Shape<'a> for Element {
+    fn points(&'a self) -> Box<dyn Iterator<Item = (f32, f32)> + 'a> {
+        match self {
+            Element::Line(line) => {
+                Box::new(line.points().chain(Some((line.x2, line.y2))))
+            }
+            Element::Circle(circle) => circle.points(),
+            Element::Rectangle(rect) => rect.points(),
+            Element::Polyline(polyline) => polyline.points(),
+            Element::Polygon(polygon) => polygon.points(),
+            Element::Path(path) => path.points(),
+        }
+    }
+
+    fn points_at_resolution(
+        &'a self,
+        dot_size: f32,
+    ) -> Box<dyn Iterator<Item = (f32, f32)> + 'a> {
+        match self {
+            Element::Line(line) => Box::new(
+                line.points_at_resolution(dot_size)
+                    .chain(Some((line.x2, line.y2))),
+            ),
+            Element::Circle(circle) => circle.points_at_resolution(dot_size),
+            Element::Rectangle(rect) => rect.points_at_resolution(dot_size),
+            Element::Polyline(polyline) => {
+                polyline.points_at_resolution(dot_size)
+            }
+            Element::Polygon(polygon) => polygon.points_at_resolution(dot_size),
+            Element::Path(path) => path.points_at_resolution(dot_size),
+        }
+    }
+
+    fn outlines(&'a self, dot_size: f32) -> Vec<Outline> {
+        match self {
+            Element::Line(line) => line.outlines(dot_size),
+            Element::Circle(circle) => circle.outlines(dot_size),
+            Element::Rectangle(rect) => rect.outlines(dot_size),
+            Element::Polyline(polyline) => polyline.outlines(dot_size),
+            Element::Polygon(polygon) => polygon.outlines(dot_size),
+            Element::Path(path) => path.outlines(dot_size),
+        }
+    }
+
+    fn rasterize(&'a self, raster: &mut Raster) {
+        match self {
+            Element::Line(line) => line.rasterize(raster),
+            Element::Circle(circle) => circle.rasterize(raster),
+            Element::Rectangle(rect) => rect.rasterize(raster),
+            Element::Polyline(polyline) => polyline.rasterize(raster),
+            Element::Polygon(polygon) => polygon.rasterize(raster),
+            Element::Path(path) => path.rasterize(raster),
+        }
+    }
+
+    fn bounding_box(&'a self) -> Option<BoundingBox> {
+        match self {
+            Element::Line(line) => line.bounding_box(),
+            Element::Circle(circle) => circle.bounding_box(),
+            Element::Rectangle(rect) => rect.bounding_box(),
+            Element::Polyline(polyline) => polyline.bounding_box(),
+            Element::Polygon(polygon) => polygon.bounding_box(),
+            Element::Path(path) => path.bounding_box(),
+        }
+    }
+}
+
+impl<'a> Shape<'a> for Svg {
+    fn points(&'a self) -> Box<dyn Iterator<Item = (f32, f32)> + 'a> {
+        Box::new(self.elements.iter().flat_map(|element| element.points()))
+    }
+
+    fn points_at_resolution(
+        &'a self,
+        dot_size: f32,
+    ) -> Box<dyn Iterator<Item = (f32, f32)> + 'a> {
+        Box::new(
+            self.elements.iter().flat_map(move |element| {
+                element.points_at_resolution(dot_size)
+            }),
+        )
+    }
+
+    fn outlines(&'a self, dot_size: f32) -> Vec<Outline> {
+        self.elements
+            .iter()
+            .flat_map(|element| element.outlines(dot_size))
+            .collect()
+    }
+
+    fn rasterize(&'a self, raster: &mut Raster) {
+        for element in self.elements.iter() {
+            element.rasterize(raster);
+        }
+    }
+
+    fn bounding_box(&'a self) -> Option<BoundingBox> {
+        self.elements
+            .iter()
+            .filter_map(|element| element.bounding_box())
+            .reduce(|a, b| a.union(&b))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn parse_elements() {
+        let svg = r#"<?xml version="1.0"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="40" height="20">
+  <!-- <line x1="bad"/> -->
+  <line x1="0" y1="0" x2="10" y2="10"/>
+  <circle cx="5" cy="5" r="2"></circle>
+  <rect x="1" y="2" width="3px" height="4"/>
+  <polyline points="0,0 1,1 2,0"/>
+  <polygon points="0 0, 1 1, 2 0"/>
+  <path d="M0 0 L 1 1"/>
+  <text x="1">ignored</text>
+</svg>"#;
+        let svg = Svg::parse(svg).unwrap();
+        assert_eq!(svg.elements.len(), 6);
+        match &svg.elements[2] {
+            Element::Rectangle(r) => {
+                assert_eq!((r.x1, r.y1, r.x2, r.y2), (1.0, 2.0, 4.0, 6.0))
+            }
+            _ => panic!("expecting a rect"),
+        }
+        match &svg.elements[4] {
+            Element::Polygon(p) => assert_eq!(p.points.len(), 3),
+            _ => panic!("expecting a polygon"),
+        }
+    }
+
+    #[test]
+    fn errors_point_into_the_document() {
+        let svg = r#"<svg><path d="M 0 0 L 1 x"/></svg>"#;
+        let err = Svg::parse(svg).err().unwrap();
+        assert_eq!(err.position, svg.find('x').unwrap());
+
+        let svg = r#"<svg><circle r="abc"/></svg>"#;
+        let err = Svg::parse(svg).err().unwrap();
+        assert_eq!(err.position, svg.find("abc").unwrap());
+    }
+
+    #[test]
+    fn draw_svg() {
+        let svg = Svg::parse(
+            r#"<svg><rect x="0" y="0" width="3.5" height="1.75"/></svg>"#,
+        )
+        .unwrap();
+        let mut context = Context::new(4.0, 2.0);
+        context.draw(&svg);
+        let result = context.to_string();
+        println!("{}", result);
+        let expected = ["⡏⠉⠉⠉⠉⠉⠉⡇", "⣇⣀⣀⣀⣀⣀⣀⡇"];
+        assert_eq!(result, expected.join("\n"));
+    }
+}
@@ -0,0 +1,484 @@
+use super::ParseError;
+use crate::BoundingBox;
+use crate::CubicBezier;
+use crate::EllipticalArc;
+use crate::Line;
+use crate::Outline;
+use crate::QuadBezier;
+use crate::Shape;
+use std::str::FromStr;
+
+/// A single drawing command of a path, in absolute coordinates
+pub enum Segment {
+    Line(Line),
+    Quad(QuadBezier),
+    Cubic(CubicBezier),
+    Arc(EllipticalArc),
+}
+
+/// A shape built from svg path data, ie: the `d` attribute of a `<path>`
+pub struct Path {
+    pub segments: Vec<Segment>,
+}
+
+impl Path {
+    /// parse the svg path data such as `M 0 0 L 10 10 Z`
+    pub fn parse(d: &str) -> Result<Self, ParseError> {
+        Parser::new(d, 0).path()
+    }
+}
+
+impl FromStr for Path {
+    type Err = ParseError;
+
+    fn from_str(d: &str) -> Result<Self, Self::Err> {
+        Path::parse(d)
+    }
+}
+
+impl<'a> Shape<'a> for Segment {
+    fn points(&'a self) -> Box<dyn Iterator<Item = (f32, f32)> + 'a> {
+        match self {
+            // the line iterator excludes the end point
+            Segment::Line(line) => {
+                Box::new(line.into_iter().chain(Some((line.x2, line.y2))))
+            }
+            Segment::Quad(quad) => quad.points(),
+            Segment::Cubic(cubic) => cubic.points(),
+            Segment::Arc(arc) => arc.points(),
+        }
+    }
+
+    fn points_at_resolution(
+        &'a self,
+        dot_size: f32,
+    ) -> Box<dyn Iterator<Item = (f32, f32)> + 'a> {
+        match self {
+            Segment::Line(line) => Box::new(
+                line.iter_with_step(dot_size)
+                    .chain(Some((line.x2, line.y2))),
+            ),
+            Segment::Quad(quad) => quad.points_at_resolution(dot_size),
+            Segment::Cubic(cubic) => cubic.points_at_resolution(dot_size),
+            Segment::Arc(arc) => arc.points_at_resolution(dot_size),
+        }
+    }
+
+    fn outlines(&'a self, dot_size: f32) -> Vec<Outline> {
+        match self {
+            Segment::Line(line) => line.outlines(dot_size),
+            Segment::Quad(quad) => quad.outlines(dot_size),
+            Segment::Cubic(cubic) => cubic.outlines(dot_size),
+            Segment::Arc(arc) => arc.outlines(dot_size),
+        }
+    }
+
+    fn bounding_box(&'a self) -> Option<BoundingBox> {
+        match self {
+            Segment::Line(line) => line.bounding_box(),
+            Segment::Quad(quad) => quad.bounding_box(),
+            Segment::Cubic(cubic) => cubic.bounding_box(),
+            Segment::Arc(arc) => arc.bounding_box(),
+        }
+    }
+}
+
+impl<'a> Shape<'a> for Path {
+    fn points(&'a self) -> Box<dyn Iterator<Item = (f32, f32)> + 'a> {
+        Box::new(self.segments.iter().flat_map(|segment| segment.points()))
+    }
+
+    fn points_at_resolution(
+        &'a self,
+        dot_size: f32,
+    ) -> Box<dyn Iterator<Item = (f32, f32)> + 'a> {
+        Box::new(
+            self.segments.iter().flat_map(move |segment| {
+                segment.points_at_resolution(dot_size)
+            }),
+        )
+    }
+
+    /// Connected segments are joined into one outline, which is closed
+    /// when it ends where it started
+    fn outlines(&'a self, dot_size: f32) -> Vec<Outline> {
+        let mut runs: Vec<Vec<(f32, f32)>> = vec![];
+        for outline in self.segments.iter().flat_map(|s| s.outlines(dot_size)) {
+            match runs.last_mut() {
+                Some(run) if run.last() == outline.vertices.first() => {
+                    run.extend(outline.vertices.into_iter().skip(1));
+                }
+                _ => runs.push(outline.vertices),
+            }
+        }
+        runs.into_iter()
+            .map(|mut run| {
+                if run.len() > 2 && run.first() == run.last() {
+                    run.pop();
+                    Outline::closed(run)
+                } else {
+                    Outline::open(run)
+                }
+            })
+            .collect()
+    }
+
+    fn bounding_box(&'a self) -> Option<BoundingBox> {
+        self.segments
+            .iter()
+            .filter_map(|segment| segment.bounding_box())
+            .reduce(|a, b| a.union(&b))
+    }
+}
+
+/// Parses svg path data and the number lists used in svg attributes.
+/// Positions in the errors are offset by `offset`, so errors can point to
+/// the location in the whole svg document.
+pub(super) struct Parser<'a> {
+    input: &'a [u8],
+    pos: usize,
+    offset: usize,
+}
+
+impl<'a> Parser<'a> {
+    pub(super) fn new(input: &'a str, offset: usize) -> Self {
+        Parser {
+            input: input.as_bytes(),
+            pos: 0,
+            offset,
+        }
+    }
+
+    fn error(&self, message: &str) -> ParseError {
+        ParseError {
+            position: self.offset + self.pos,
+            message: message.to_string(),
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.pos).copied()
+    }
+
+    pub(super) fn at_end(&self) -> bool {
+        self.pos >= self.input.len()
+    }
+
+    /// skip whitespaces and commas in between numbers
+    pub(super) fn skip_separators(&mut self) {
+        while let Some(b) = self.peek() {
+            if b.is_ascii_whitespace() || b == b',' {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// whether the next token is a number
+    fn has_number(&mut self) -> bool {
+        self.skip_separators();
+        match self.peek() {
+            Some(b) => {
+                b.is_ascii_digit() || b == b'-' || b == b'+' || b == b'.'
+            }
+            None => false,
+        }
+    }
+
+    fn digits(&mut self) -> usize {
+        let start = self.pos;
+        while let Some(b'0'..=b'9') = self.peek() {
+            self.pos += 1;
+        }
+        self.pos - start
+    }
+
+    pub(super) fn number(&mut self) -> Result<f32, ParseError> {
+        self.skip_separators();
+        let start = self.pos;
+        if let Some(b'-') | Some(b'+') = self.peek() {
+            self.pos += 1;
+        }
+        let mut count = self.digits();
+        if let Some(b'.') = self.peek() {
+            self.pos += 1;
+            count += self.digits();
+        }
+        if count == 0 {
+            self.pos = start;
+            return Err(self.error("expecting a number"));
+        }
+        if let Some(b'e') | Some(b'E') = self.peek() {
+            let mantissa_end = self.pos;
+            self.pos += 1;
+            if let Some(b'-') | Some(b'+') = self.peek() {
+                self.pos += 1;
+            }
+            if self.digits() == 0 {
+                self.pos = mantissa_end;
+            }
+        }
+        let text = std::str::from_utf8(&self.input[start..self.pos])
+            .expect("must be ascii");
+        text.parse().map_err(|_| {
+            self.pos = start;
+            self.error("invalid number")
+        })
+    }
+
+    fn pair(&mut self) -> Result<(f32, f32), ParseError> {
+        Ok((self.number()?, self.number()?))
+    }
+
+    /// arc flags are a single 0 or 1 which may not be separated from the
+    /// next number
+    fn flag(&mut self) -> Result<bool, ParseError> {
+        self.skip_separators();
+        match self.peek() {
+            Some(b'0') => {
+                self.pos += 1;
+                Ok(false)
+            }
+            Some(b'1') => {
+                self.pos += 1;
+                Ok(true)
+            }
+            _ => Err(self.error("expecting a flag of 0 or 1")),
+        }
+    }
+
+    /// parse a list of coordinate pairs such as the `points` attribute of
+    /// `<polyline>` and `<polygon>`
+    pub(super) fn points(&mut self) -> Result<Vec<(f32, f32)>, ParseError> {
+        let mut points = vec![];
+        while self.has_number() {
+            points.push(self.pair()?);
+        }
+        if !self.at_end() {
+            return Err(self.error("expecting a number"));
+        }
+        Ok(points)
+    }
+
+    pub(super) fn path(&mut self) -> Result<Path, ParseError> {
+        let mut segments = vec![];
+        let mut current = (0.0, 0.0);
+        let mut start = (0.0, 0.0);
+        // the last control point, used for the reflection in S and T
+        let mut last_cubic: Option<(f32, f32)> = None;
+        let mut last_quad: Option<(f32, f32)> = None;
+        let mut last_command: Option<u8> = None;
+
+        self.skip_separators();
+        while !self.at_end() {
+            let command = match self.peek() {
+                Some(b) if b.is_ascii_alphabetic() => {
+                    self.pos += 1;
+                    b
+                }
+                _ => match last_command {
+                    // numbers after a moveto are implicit lineto
+                    Some(b'M') => b'L',
+                    Some(b'm') => b'l',
+                    Some(b'Z') | Some(b'z') | None => {
+                        return Err(self.error("expecting a command"));
+                    }
+                    Some(c) => c,
+                },
+            };
+            if last_command.is_none() && command != b'M' && command != b'm' {
+                self.pos -= 1;
+                return Err(self.error("path must start with a moveto"));
+            }
+            let relative = command.is_ascii_lowercase();
+            let abs = move |(x, y): (f32, f32)| {
+                if relative {
+                    (current.0 + x, current.1 + y)
+                } else {
+                    (x, y)
+                }
+            };
+            let mut cubic_control = None;
+            let mut quad_control = None;
+            match command.to_ascii_uppercase() {
+                b'M' => {
+                    current = abs(self.pair()?);
+                    start = current;
+                }
+                b'L' => {
+                    let end = abs(self.pair()?);
+                    segments.push(line(current, end));
+                    current = end;
+                }
+                b'H' => {
+                    let x = self.number()?;
+                    let end = if relative {
+                        (current.0 + x, current.1)
+                    } else {
+                        (x, current.1)
+                    };
+                    segments.push(line(current, end));
+                    current = end;
+                }
+                b'V' => {
+                    let y = self.number()?;
+                    let end = if relative {
+                        (current.0, current.1 + y)
+                    } else {
+                        (current.0, y)
+                    };
+                    segments.push(line(current, end));
+                    current = end;
+                }
+                b'C' | b'S' => {
+                    let c1 = if command.eq_ignore_ascii_case(&b'C') {
+                        abs(self.pair()?)
+                    } else {
+                        reflect(last_cubic, current)
+                    };
+                    let c2 = abs(self.pair()?);
+                    let end = abs(self.pair()?);
+                    segments.push(Segment::Cubic(CubicBezier::new(
+                        current, c1, c2, end,
+                    )));
+                    cubic_control = Some(c2);
+                    current = end;
+                }
+                b'Q' | b'T' => {
+                    let c = if command.eq_ignore_ascii_case(&b'Q') {
+                        abs(self.pair()?)
+                    } else {
+                        reflect(last_quad, current)
+                    };
+                    let end = abs(self.pair()?);
+                    segments
+                        .push(Segment::Quad(QuadBezier::new(current, c, end)));
+                    quad_control = Some(c);
+                    current = end;
+                }
+                b'A' => {
+                    let rx = self.number()?;
+                    let ry = self.number()?;
+                    let x_axis_rotation = self.number()?;
+                    let large_arc_flag = self.flag()?;
+                    let sweep_flag = self.flag()?;
+                    let end = abs(self.pair()?);
+                    segments.push(Segment::Arc(EllipticalArc::new(
+                        current.0,
+                        current.1,
+                        end.0,
+                        end.1,
+                        rx,
+                        ry,
+                        x_axis_rotation,
+                        large_arc_flag,
+                        sweep_flag,
+                    )));
+                    current = end;
+                }
+                b'Z' => {
+                    if current != start {
+                        segments.push(line(current, start));
+                    }
+                    current = start;
+                }
+                _ => {
+                    self.pos -= 1;
+                    return Err(self.error("unknown command"));
+                }
+            }
+            last_cubic = cubic_control;
+            last_quad = quad_control;
+            last_command = Some(command);
+            self.skip_separators();
+        }
+        Ok(Path { segments })
+    }
+}
+
+fn line((x1, y1): (f32, f32), (x2, y2): (f32, f32)) -> Segment {
+    Segment::Line(Line::new(x1, y1, x2, y2))
+}
+
+/// the reflection of the previous control point about the current point,
+/// or the current point if the previous command has no control point
+fn reflect(control: Option<(f32, f32)>, current: (f32, f32)) -> (f32, f32) {
+    match control {
+        Some((x, y)) => (2.0 * current.0 - x, 2.0 * current.1 - y),
+        None => current,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn parse_relative_and_implicit_commands() {
+        let path = Path::parse("m1,1 2 0 0-.5e1 h-2z").unwrap();
+        let lines: Vec<(f32, f32, f32, f32)> = path
+            .segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Line(l) => (l.x1, l.y1, l.x2, l.y2),
+                _ => panic!("expecting only lines"),
+            })
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                (1.0, 1.0, 3.0, 1.0),
+                (3.0, 1.0, 3.0, -4.0),
+                (3.0, -4.0, 1.0, -4.0),
+                (1.0, -4.0, 1.0, 1.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_curves_and_compact_arc_flags() {
+        let path =
+            Path::parse("M0 0C1 1 2 1 3 0S5-1 6 0Q7 1 8 0T10 0A2 2 0 013 4")
+                .unwrap();
+        assert_eq!(path.segments.len(), 5);
+        match &path.segments[1] {
+            Segment::Cubic(c) => assert_eq!((c.cx1, c.cy1), (4.0, -1.0)),
+            _ => panic!("expecting a cubic"),
+        }
+        match &path.segments[3] {
+            Segment::Quad(q) => assert_eq!((q.cx, q.cy), (9.0, -1.0)),
+            _ => panic!("expecting a quad"),
+        }
+        match &path.segments[4] {
+            Segment::Arc(a) => {
+                assert!(!a.large_arc_flag);
+                assert!(a.sweep_flag);
+                assert_eq!((a.x2, a.y2), (3.0, 4.0));
+            }
+            _ => panic!("expecting an arc"),
+        }
+    }
+
+    #[test]
+    fn errors_have_positions() {
+        let err = Path::parse("L 1 1").err().unwrap();
+        assert_eq!(err.position, 0);
+        let err = Path::parse("M 1 1 L 2 x").err().unwrap();
+        assert_eq!(err.position, 10);
+        let err = Path::parse("M 1 1 K 2").err().unwrap();
+        assert_eq!(err.position, 6);
+    }
+
+    #[test]
+    fn draw_path() {
+        let mut context = Context::new(4.0, 2.0);
+        let path: Path = "M0 0 H3.5 V1.75 H0 Z".parse().unwrap();
+        context.draw(&path);
+        let result = context.to_string();
+        println!("{}", result);
+        let expected = ["⡏⠉⠉⠉⠉⠉⠉⡇", "⣇⣀⣀⣀⣀⣀⣀⡇"];
+        assert_eq!(result, expected.join("\n"));
+    }
+}
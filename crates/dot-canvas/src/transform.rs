@@ -0,0 +1,264 @@
+use crate::BoundingBox;
+use crate::Outline;
+use crate::Raster;
+use crate::Shape;
+
+/// A 2D affine transformation matrix, using the same convention as the
+/// svg `matrix(a, b, c, d, e, f)` transform:
+/// ```ignore
+///     x' = a * x + c * y + e
+///     y' = b * x + d * y + f
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    pub a: f32,
+    pub b: f32,
+    pub c: f32,
+    pub d: f32,
+    pub e: f32,
+    pub f: f32,
+}
+
+/// A shape drawn with the transform applied to all of its points
+pub struct Transformed<S> {
+    pub shape: S,
+    pub transform: Transform,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Transform::IDENTITY
+    }
+}
+
+impl Transform {
+    pub const IDENTITY: Transform = Transform {
+        a: 1.0,
+        b: 0.0,
+        c: 0.0,
+        d: 1.0,
+        e: 0.0,
+        f: 0.0,
+    };
+
+    pub fn new(a: f32, b: f32, c: f32, d: f32, e: f32, f: f32) -> Self {
+        Transform { a, b, c, d, e, f }
+    }
+
+    pub fn translate(tx: f32, ty: f32) -> Self {
+        Transform::new(1.0, 0.0, 0.0, 1.0, tx, ty)
+    }
+
+    pub fn scale(sx: f32, sy: f32) -> Self {
+        Transform::new(sx, 0.0, 0.0, sy, 0.0, 0.0)
+    }
+
+    /// rotate about the origin by the angle in degrees, which is clockwise
+    /// when y points down
+    pub fn rotate(degrees: f32) -> Self {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        Transform::new(cos, sin, -sin, cos, 0.0, 0.0)
+    }
+
+    /// rotate by the angle in degrees about the point (cx, cy)
+    pub fn rotate_about(degrees: f32, cx: f32, cy: f32) -> Self {
+        Transform::translate(-cx, -cy)
+            .then(&Transform::rotate(degrees))
+            .then(&Transform::translate(cx, cy))
+    }
+
+    /// The transform which applies this transform first and then `other`
+    pub fn then(&self, other: &Transform) -> Transform {
+        Transform {
+            a: other.a * self.a + other.c * self.b,
+            b: other.b * self.a + other.d * self.b,
+            c: other.a * self.c + other.c * self.d,
+            d: other.b * self.c + other.d * self.d,
+            e: other.a * self.e + other.c * self.f + other.e,
+            f: other.b * self.e + other.d * self.f + other.f,
+        }
+    }
+
+    pub fn apply(&self, (x, y): (f32, f32)) -> (f32, f32) {
+        (
+            self.a * x + self.c * y + self.e,
+            self.b * x + self.d * y + self.f,
+        )
+    }
+
+    /// the inverse transform, None if this transform collapses the plane
+    /// into a line or a point
+    pub fn inverse(&self) -> Option<Transform> {
+        let det = self.a * self.d - self.b * self.c;
+        if det == 0.0 {
+            return None;
+        }
+        Some(Transform {
+            a: self.d / det,
+            b: -self.b / det,
+            c: -self.c / det,
+            d: self.a / det,
+            e: (self.c * self.f - self.d * self.e) / det,
+            f: (self.b * self.e - self.a * self.f) / det,
+        })
+    }
+
+    /// The most that a length is stretched by this transform, used to
+    /// sample shapes finely enough after they are scaled up
+    pub fn max_scale(&self) -> f32 {
+        let t = self.a * self.a
+            + self.b * self.b
+            + self.c * self.c
+            + self.d * self.d;
+        let det = self.a * self.d - self.b * self.c;
+        ((t + (t * t - 4.0 * det * det).max(0.0).sqrt()) / 2.0).sqrt()
+    }
+
+    /// the resolution to sample the shape at, so that it has no gaps after
+    /// it is transformed
+    pub(crate) fn local_dot_size(&self, dot_size: f32) -> f32 {
+        let scale = self.max_scale();
+        if scale > 0.0 {
+            dot_size / scale
+        } else {
+            dot_size
+        }
+    }
+}
+
+impl<S> Transformed<S> {
+    pub fn new(shape: S, transform: Transform) -> Self {
+        Transformed { shape, transform }
+    }
+}
+
+impl<'a, S> Shape<'a> for Transformed<S>
+where
+    S: Shape<'a>,
+{
+    fn points(&'a self) -> Box<dyn Iterator<Item = (f32, f32)> + 'a> {
+        let transform = self.transform;
+        Box::new(self.shape.points().map(move |p| transform.apply(p)))
+    }
+
+    fn points_at_resolution(
+        &'a self,
+        dot_size: f32,
+    ) -> Box<dyn Iterator<Item = (f32, f32)> + 'a> {
+        let transform = self.transform;
+        Box::new(
+            self.shape
+                .points_at_resolution(transform.local_dot_size(dot_size))
+                .map(move |p| transform.apply(p)),
+        )
+    }
+
+    fn outlines(&'a self, dot_size: f32) -> Vec<Outline> {
+        let mut outlines =
+            self.shape.outlines(self.transform.local_dot_size(dot_size));
+        for outline in outlines.iter_mut() {
+            for vertex in outline.vertices.iter_mut() {
+                *vertex = self.transform.apply(*vertex);
+            }
+        }
+        outlines
+    }
+
+    fn rasterize(&'a self, raster: &mut Raster) {
+        raster.with_transform(&self.transform, |raster| {
+            self.shape.rasterize(raster)
+        });
+    }
+
+    fn fill(&'a self, raster: &mut Raster) {
+        raster
+            .with_transform(&self.transform, |raster| self.shape.fill(raster));
+    }
+
+    /// the box around the transformed corners of the box of the shape,
+    /// which is larger than the shape when it is rotated
+    fn bounding_box(&'a self) -> Option<BoundingBox> {
+        let bounding_box = self.shape.bounding_box()?;
+        BoundingBox::from_points(
+            bounding_box
+                .corners()
+                .iter()
+                .map(|corner| self.transform.apply(*corner)),
+        )
+    }
+
+    fn contains(&'a self, x: f32, y: f32) -> bool {
+        match self.transform.inverse() {
+            Some(inverse) => {
+                let (x, y) = inverse.apply((x, y));
+                self.shape.contains(x, y)
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    fn assert_close((x1, y1): (f32, f32), (x2, y2): (f32, f32)) {
+        assert!(
+            (x1 - x2).abs() < 1e-4 && (y1 - y2).abs() < 1e-4,
+            "{:?} != {:?}",
+            (x1, y1),
+            (x2, y2)
+        );
+    }
+
+    #[test]
+    fn compose_and_invert() {
+        let t = Transform::scale(2.0, 3.0)
+            .then(&Transform::rotate(90.0))
+            .then(&Transform::translate(1.0, 1.0));
+        assert_close(t.apply((1.0, 1.0)), (-2.0, 3.0));
+        let inverse = t.inverse().unwrap();
+        assert_close(inverse.apply((-2.0, 3.0)), (1.0, 1.0));
+        assert_close(
+            Transform::rotate_about(180.0, 1.0, 1.0).apply((0.0, 0.0)),
+            (2.0, 2.0),
+        );
+        assert!((t.max_scale() - 3.0).abs() < 1e-4);
+        assert!(Transform::scale(0.0, 1.0).inverse().is_none());
+    }
+
+    #[test]
+    fn draw_transformed_shape() {
+        let mut context = Context::new(4.0, 2.0);
+        let line = Line::new(0.0, 0.0, 1.0, 0.0);
+        let transform = Transform::scale(3.0, 1.0)
+            .then(&Transform::rotate(90.0))
+            .then(&Transform::translate(1.125, 0.0));
+        context.draw(&Transformed::new(line, transform));
+        let result = context.to_string();
+        println!("{}", result);
+        let expected = ["  ⡇     ", "  ⡇     "];
+        assert_eq!(result, expected.join("\n"));
+    }
+
+    #[test]
+    fn push_and_pop_transforms() {
+        let mut context = Context::new(4.0, 2.0);
+        context.push_transform(Transform::translate(2.0, 0.0));
+        context.push_transform(Transform::scale(1.0, 2.0));
+        assert_eq!(
+            context.transform(),
+            Transform::new(1.0, 0.0, 0.0, 2.0, 2.0, 0.0)
+        );
+        context.draw(&Line::new(0.0, 0.0, 0.0, 1.0));
+        context.pop_transform();
+        context.draw(&Line::new(0.0, 0.0, 1.0, 0.0));
+        context.pop_transform();
+        assert_eq!(context.transform(), Transform::IDENTITY);
+        let result = context.to_string();
+        println!("{}", result);
+        let expected = ["    ⡏⠉⠁ ", "    ⡇   "];
+        assert_eq!(result, expected.join("\n"));
+    }
+}
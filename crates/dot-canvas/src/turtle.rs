@@ -0,0 +1,277 @@
+//! Turtle graphics, where the lines are drawn by moving a turtle around,
+//! and L-systems which generate the moves of the turtle
+use crate::shape::STEP;
+use crate::Outline;
+use crate::Polyline;
+use crate::Shape;
+use std::collections::HashMap;
+
+/// The position, heading and pen of the turtle
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct State {
+    x: f32,
+    y: f32,
+    /// the direction in degrees, where 0 is along the positive x axis
+    heading: f32,
+    pen_down: bool,
+}
+
+/// A turtle which records the lines it draws while moving, as a shape
+pub struct Turtle {
+    state: State,
+    /// the states saved by `push`
+    stack: Vec<State>,
+    /// the lines drawn, where a new line starts whenever the pen is lifted
+    /// or the turtle jumps
+    paths: Vec<Polyline>,
+}
+
+impl Turtle {
+    /// A turtle at (x, y) heading along the positive x axis, with the pen
+    /// down
+    pub fn new(x: f32, y: f32) -> Self {
+        Turtle {
+            state: State {
+                x,
+                y,
+                heading: 0.0,
+                pen_down: true,
+            },
+            stack: vec![],
+            paths: vec![],
+        }
+    }
+
+    pub fn position(&self) -> (f32, f32) {
+        (self.state.x, self.state.y)
+    }
+
+    /// the direction in degrees, where 0 is along the positive x axis
+    pub fn heading(&self) -> f32 {
+        self.state.heading
+    }
+
+    pub fn set_heading(&mut self, degrees: f32) {
+        self.state.heading = degrees;
+    }
+
+    /// Move forward by the distance, drawing a line when the pen is down
+    pub fn forward(&mut self, distance: f32) {
+        let (sin, cos) = self.state.heading.to_radians().sin_cos();
+        let from = self.position();
+        self.state.x += distance * cos;
+        self.state.y += distance * sin;
+        if !self.state.pen_down {
+            return;
+        }
+        let to = self.position();
+        match self.paths.last_mut() {
+            Some(path) if path.points.last() == Some(&from) => {
+                path.points.push(to)
+            }
+            _ => self.paths.push(Polyline::new(&[from, to])),
+        }
+    }
+
+    pub fn back(&mut self, distance: f32) {
+        self.forward(-distance);
+    }
+
+    /// Turn towards the positive angle direction, which is counter
+    /// clockwise when the y axis goes up
+    pub fn left(&mut self, degrees: f32) {
+        self.state.heading += degrees;
+    }
+
+    /// Turn towards the negative angle direction, which is clockwise when
+    /// the y axis goes up
+    pub fn right(&mut self, degrees: f32) {
+        self.state.heading -= degrees;
+    }
+
+    /// Lift the pen, so moving doesn't draw
+    pub fn pen_up(&mut self) {
+        self.state.pen_down = false;
+    }
+
+    pub fn pen_down(&mut self) {
+        self.state.pen_down = true;
+    }
+
+    /// Save the position, heading and pen
+    pub fn push(&mut self) {
+        self.stack.push(self.state);
+    }
+
+    /// Restore the position, heading and pen of the last `push`
+    pub fn pop(&mut self) {
+        if let Some(state) = self.stack.pop() {
+            self.state = state;
+        }
+    }
+
+    /// the lines drawn so far
+    pub fn paths(&self) -> &[Polyline] {
+        &self.paths
+    }
+
+    /// Follow the commands of an L-system, where `F` and `G` move forward
+    /// by `step` drawing a line, `f` moves forward without drawing, `+`
+    /// turns left by `angle` degrees, `-` turns right, `|` turns around,
+    /// `[` pushes and `]` pops the state. Other characters are ignored.
+    pub fn run(&mut self, commands: &str, step: f32, angle: f32) {
+        for command in commands.chars() {
+            match command {
+                'F' | 'G' => self.forward(step),
+                'f' => {
+                    let pen_down = self.state.pen_down;
+                    self.pen_up();
+                    self.forward(step);
+                    self.state.pen_down = pen_down;
+                }
+                '+' => self.left(angle),
+                '-' => self.right(angle),
+                '|' => self.left(180.0),
+                '[' => self.push(),
+                ']' => self.pop(),
+                _ => (),
+            }
+        }
+    }
+}
+
+impl<'a> Shape<'a> for Turtle {
+    fn points(&'a self) -> Box<dyn Iterator<Item = (f32, f32)> + 'a> {
+        self.points_at_resolution(STEP)
+    }
+
+    fn points_at_resolution(
+        &'a self,
+        dot_size: f32,
+    ) -> Box<dyn Iterator<Item = (f32, f32)> + 'a> {
+        Box::new(
+            self.paths
+                .iter()
+                .flat_map(move |path| path.points_at_resolution(dot_size)),
+        )
+    }
+
+    fn outlines(&'a self, dot_size: f32) -> Vec<Outline> {
+        self.paths
+            .iter()
+            .flat_map(|path| path.outlines(dot_size))
+            .collect()
+    }
+}
+
+/// A Lindenmayer system, which rewrites every character of the axiom with
+/// its rule at each iteration
+pub struct LSystem {
+    pub axiom: String,
+    /// the replacement of each character, characters without a rule are
+    /// kept
+    pub rules: HashMap<char, String>,
+}
+
+impl LSystem {
+    pub fn new(axiom: &str) -> Self {
+        LSystem {
+            axiom: axiom.to_string(),
+            rules: HashMap::new(),
+        }
+    }
+
+    /// Rewrite the character with the replacement at each iteration
+    pub fn add_rule(&mut self, ch: char, replacement: &str) {
+        self.rules.insert(ch, replacement.to_string());
+    }
+
+    /// the commands after rewriting the axiom the number of iterations
+    pub fn expand(&self, iterations: usize) -> String {
+        let mut commands = self.axiom.clone();
+        for _ in 0..iterations {
+            let mut next = String::with_capacity(commands.len());
+            for ch in commands.chars() {
+                match self.rules.get(&ch) {
+                    Some(replacement) => next.push_str(replacement),
+                    None => next.push(ch),
+                }
+            }
+            commands = next;
+        }
+        commands
+    }
+
+    /// Expand the axiom and let the turtle follow the commands, see
+    /// `Turtle::run`
+    pub fn draw(
+        &self,
+        turtle: &mut Turtle,
+        iterations: usize,
+        step: f32,
+        angle: f32,
+    ) {
+        turtle.run(&self.expand(iterations), step, angle);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn turtle_square_and_jumps() {
+        let mut turtle = Turtle::new(0.25, 0.25);
+        for _ in 0..4 {
+            turtle.forward(1.5);
+            turtle.left(90.0);
+        }
+        turtle.left(90.0);
+        turtle.pen_up();
+        turtle.forward(0.75);
+        turtle.pen_down();
+        turtle.push();
+        turtle.right(90.0);
+        turtle.forward(0.75);
+        turtle.pop();
+        turtle.pen_up();
+        turtle.forward(0.5);
+        let (x, y) = turtle.position();
+        assert!((x - 0.25).abs() < 1e-4 && (y - 1.5).abs() < 1e-4);
+        assert_eq!(turtle.paths().len(), 2);
+        assert_eq!(turtle.paths()[0].points.len(), 5);
+        let mut context = Context::new(2.0, 1.0);
+        context.set_y_bounds(0.0, 2.0);
+        context.set_orientation(Orientation::YUp);
+        context.draw(&turtle);
+        let result = context.to_string();
+        println!("{}", result);
+        let expected = ["⢸⣭⣍⣹"];
+        assert_eq!(result, expected.join("\n"));
+    }
+
+    #[test]
+    fn koch_curve() {
+        let mut koch = LSystem::new("F");
+        koch.add_rule('F', "F+F-F-F+F");
+        assert_eq!(koch.expand(1), "F+F-F-F+F");
+        assert_eq!(koch.expand(2).matches('F').count(), 25);
+
+        let mut turtle = Turtle::new(0.5, 0.5);
+        koch.draw(&mut turtle, 2, 1.0, 90.0);
+        let mut context = Context::with_bounds(20, 5, (0.0, 10.0), (0.0, 5.0));
+        context.set_orientation(Orientation::YUp);
+        context.draw(&turtle);
+        let result = context.to_string();
+        println!("{}", result);
+        let expected = [
+            "         ⡤⠤⡄        ",
+            "       ⡤⠤⠇ ⠧⠤⡄      ",
+            "     ⡤⠤⠇     ⠧⠤⡄    ",
+            "   ⡤⠤⡧⠤⡄     ⡤⠤⡧⠤⡄  ",
+            " ⠤⠤⠇ ⠧⠤⠇     ⠧⠤⠇ ⠧⠤⠄",
+        ];
+        assert_eq!(result, expected.join("\n"));
+    }
+}
@@ -15,6 +15,12 @@ pub struct Canvas {
     cells: HashMap<(usize, usize), Vec<Fragment>>,
 }
 
+impl Default for Canvas {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Canvas {
     pub fn new() -> Self {
         Canvas {
@@ -206,14 +212,14 @@ impl Canvas {
         let mut fragments = fragments.to_owned();
         fragments.sort();
         fragments.dedup();
-        FRAGMENT_CHAR.get(&fragments).map(|c| *c)
+        FRAGMENT_CHAR.get(&fragments).copied()
     }
 
     pub fn get_cells<'a>(
         &'a self,
     ) -> Box<dyn Iterator<Item = (usize, usize, char)> + 'a> {
         let mut cells = self.cells.iter().collect::<Vec<_>>();
-        cells.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.cmp(&b.1)));
+        cells.sort_by(|a, b| a.0.cmp(b.0).then(a.1.cmp(b.1)));
         Box::new(cells.into_iter().flat_map(|((x, y), frags)| {
             Self::resolve(frags).map(|ch| (*x, *y, ch))
         }))
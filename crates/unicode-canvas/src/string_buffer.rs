@@ -1,3 +1,4 @@
+use std::fmt;
 use std::ops::Deref;
 use std::ops::DerefMut;
 
@@ -48,15 +49,15 @@ impl StringBuffer {
             let column = &self[y as usize];
             let column_diff = x - column.len() as i32;
             if column_diff >= 0 {
-                self.add_column(row_index as usize, column_diff + 1);
+                self.add_column(row_index, column_diff + 1);
             }
             self[row_index][column_index] = ch;
         }
     }
 }
 
-impl ToString for StringBuffer {
-    fn to_string(&self) -> String {
+impl fmt::Display for StringBuffer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let mut lines = vec![];
         for row in self.iter() {
             let row_contents: Vec<String> = row
@@ -67,6 +68,6 @@ impl ToString for StringBuffer {
             let line = row_contents.join("").trim_end().to_string();
             lines.push(line);
         }
-        lines.join("\n")
+        write!(f, "{}", lines.join("\n"))
     }
 }